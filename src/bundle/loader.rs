@@ -6,6 +6,7 @@ use std::path::{Path, PathBuf};
 
 use serde::Deserialize;
 
+use crate::graphics::text::DEFAULT_FONT;
 use crate::skin::{LoadedSkin, SkinError};
 
 /// App metadata from [app] section.
@@ -232,9 +233,16 @@ impl AppBundle {
         self.action_scripts.keys()
     }
 
-    /// Load the skin from this bundle.
+    /// Load the skin from this bundle. The bundle's font becomes the skin's
+    /// default font unless the skin declares its own.
     pub fn load_skin(&self) -> Result<LoadedSkin, SkinError> {
-        LoadedSkin::load(&self.skin_path)
+        let mut skin = LoadedSkin::load(&self.skin_path)?;
+        let fonts = skin.fonts_mut();
+        if !fonts.contains(DEFAULT_FONT) {
+            fonts.load(DEFAULT_FONT, &self.font_path)?;
+        }
+        fonts.set_default_size(self.font_size);
+        Ok(skin)
     }
 
     /// Create an AppConfig compatible with the scripting module.
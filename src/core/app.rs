@@ -1,6 +1,8 @@
+use std::time::Instant;
+
 use winit::event::WindowEvent;
 
-use crate::core::View;
+use crate::core::{Rect, View};
 
 /// Trait for applications using the Crix framework.
 pub trait App {
@@ -12,6 +14,73 @@ pub trait App {
         let _ = event;
         false
     }
+
+    /// Returns when the view next needs to be redrawn without any input,
+    /// e.g. for animations. The event loop sleeps until then.
+    fn next_frame(&self) -> Option<Instant> {
+        None
+    }
+
+    /// Regions changed since the last frame. Only these are repainted and
+    /// presented; `None` (the default) repaints the whole window.
+    fn take_damage(&mut self) -> Option<Vec<Rect>> {
+        None
+    }
+
+    /// Window operations requested while handling events, applied by the
+    /// platform after each event.
+    fn take_window_requests(&mut self) -> Vec<WindowRequest> {
+        Vec::new()
+    }
+}
+
+/// Something an app asks of the window it runs in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowRequest {
+    /// Move the window with the pointer until the button is released.
+    /// Only honored while a mouse button is held down.
+    Drag,
+    Minimize,
+    Close,
+    /// Change how many screen pixels each UI pixel covers.
+    SetScale(UiScale),
+}
+
+/// How many screen pixels wide each UI pixel is drawn. Scales are whole
+/// numbers so pixel art stays crisp.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UiScale {
+    /// Follow the display's scale factor, rounded to the nearest whole number.
+    #[default]
+    Auto,
+    Fixed(u32),
+}
+
+impl UiScale {
+    /// The scale to draw at on a display with the given scale factor.
+    pub fn resolve(self, scale_factor: f64) -> u32 {
+        match self {
+            UiScale::Auto => scale_factor.round().max(1.0) as u32,
+            UiScale::Fixed(scale) => scale.max(1),
+        }
+    }
+}
+
+impl std::str::FromStr for UiScale {
+    type Err = String;
+
+    /// Parse "auto", or a scale like "2" or "2x".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("auto") {
+            return Ok(UiScale::Auto);
+        }
+        s.trim_end_matches(['x', 'X'])
+            .parse()
+            .ok()
+            .filter(|&scale| scale > 0)
+            .map(UiScale::Fixed)
+            .ok_or_else(|| format!("invalid scale '{}' (expected 'auto' or e.g. '2x')", s))
+    }
 }
 
 /// A simple app runner that wraps a View without event handling.
@@ -30,3 +99,22 @@ impl<V: View> App for AppRunner<V> {
         &self.view
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ui_scale_resolves_to_whole_numbers() {
+        assert_eq!(UiScale::Auto.resolve(1.0), 1);
+        assert_eq!(UiScale::Auto.resolve(1.5), 2);
+        assert_eq!(UiScale::Auto.resolve(0.75), 1);
+        assert_eq!(UiScale::Fixed(3).resolve(2.0), 3);
+
+        assert_eq!("auto".parse(), Ok(UiScale::Auto));
+        assert_eq!("2x".parse(), Ok(UiScale::Fixed(2)));
+        assert_eq!("3".parse(), Ok(UiScale::Fixed(3)));
+        assert!("0".parse::<UiScale>().is_err());
+        assert!("big".parse::<UiScale>().is_err());
+    }
+}
@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use image::RgbaImage;
+
+use crate::core::Rect;
+
+/// The part of a node's bounds that responds to the pointer.
+///
+/// Nodes without a hit region are hit anywhere inside their bounds.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HitRegion {
+    /// The ellipse inscribed in the bounds.
+    Ellipse,
+    /// A polygon (even-odd filled), with points relative to the top-left
+    /// corner of the bounds.
+    Polygon(Vec<(i32, i32)>),
+    /// A mask stretched over the bounds.
+    Mask(HitMask),
+}
+
+impl HitRegion {
+    /// Whether the point (in the same coordinates as `bounds`) hits the region.
+    pub fn contains(&self, bounds: &Rect, x: i32, y: i32) -> bool {
+        if !bounds.contains(x, y) {
+            return false;
+        }
+        // Test pixel centers relative to the bounds
+        let px = (x - bounds.x) as f32 + 0.5;
+        let py = (y - bounds.y) as f32 + 0.5;
+
+        match self {
+            HitRegion::Ellipse => {
+                let (rx, ry) = (bounds.width as f32 / 2.0, bounds.height as f32 / 2.0);
+                let (dx, dy) = ((px - rx) / rx, (py - ry) / ry);
+                dx * dx + dy * dy <= 1.0
+            }
+            HitRegion::Polygon(points) => {
+                let mut inside = false;
+                let mut previous = points.last().copied();
+                for &(x1, y1) in points {
+                    if let Some((x0, y0)) = previous {
+                        let (x0, y0, x1, y1) = (x0 as f32, y0 as f32, x1 as f32, y1 as f32);
+                        if (y0 > py) != (y1 > py) && px < x0 + (py - y0) * (x1 - x0) / (y1 - y0) {
+                            inside = !inside;
+                        }
+                    }
+                    previous = Some((x1, y1));
+                }
+                inside
+            }
+            HitRegion::Mask(mask) => {
+                let mx = (px / bounds.width as f32 * mask.width as f32) as u32;
+                let my = (py / bounds.height as f32 * mask.height as f32) as u32;
+                mask.get(mx, my)
+            }
+        }
+    }
+}
+
+/// Which pixels of an image are solid, shared cheaply between nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HitMask {
+    width: u32,
+    height: u32,
+    solid: Arc<[bool]>,
+}
+
+impl HitMask {
+    /// Pixels with any opacity are solid; fully transparent ones are not.
+    pub fn from_alpha(image: &RgbaImage) -> Self {
+        Self {
+            width: image.width(),
+            height: image.height(),
+            solid: image.pixels().map(|p| p.0[3] > 0).collect(),
+        }
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.solid[(y * self.width + x) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn test_regions_exclude_corners() {
+        let bounds = Rect::new(10, 10, 20, 20);
+        assert!(HitRegion::Ellipse.contains(&bounds, 20, 20));
+        assert!(!HitRegion::Ellipse.contains(&bounds, 10, 10));
+
+        let triangle = HitRegion::Polygon(vec![(0, 0), (20, 0), (0, 20)]);
+        assert!(triangle.contains(&bounds, 12, 12));
+        assert!(!triangle.contains(&bounds, 28, 28));
+
+        // A 2x2 mask with only the top-left pixel solid, stretched to 20x20
+        let mut image = RgbaImage::new(2, 2);
+        image.put_pixel(0, 0, Rgba([0, 0, 0, 255]));
+        let mask = HitRegion::Mask(HitMask::from_alpha(&image));
+        assert!(mask.contains(&bounds, 19, 19));
+        assert!(!mask.contains(&bounds, 20, 19));
+        assert!(!mask.contains(&bounds, 5, 5));
+    }
+}
@@ -0,0 +1,345 @@
+use crate::core::Rect;
+
+/// The direction a stack places its children in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Axis {
+    Horizontal,
+    #[default]
+    Vertical,
+}
+
+/// Where a child sits across a stack, or within its grid cell.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Align {
+    #[default]
+    Start,
+    Center,
+    End,
+    /// Fill the available space.
+    Stretch,
+}
+
+/// How a stack's children share the space along it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Justify {
+    #[default]
+    Start,
+    Center,
+    End,
+    /// First child at the start, last at the end, the rest evenly between.
+    SpaceBetween,
+}
+
+/// How a node follows its parent's edges when the parent is resized.
+///
+/// `Start` keeps the distance to the left (or top) edge, `End` to the right
+/// (or bottom) edge, `Center` keeps the node's offset from the middle, and
+/// `Stretch` keeps both distances by resizing the node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Anchor {
+    pub x: Align,
+    pub y: Align,
+}
+
+impl Anchor {
+    /// Where a node laid out at `rect` inside `parent` goes once the parent
+    /// is at `resized` instead.
+    pub fn place(&self, rect: &Rect, parent: &Rect, resized: &Rect) -> Rect {
+        let (x, width) = follow(
+            self.x,
+            rect.x - parent.x,
+            rect.width,
+            parent.width,
+            resized.width,
+        );
+        let (y, height) = follow(
+            self.y,
+            rect.y - parent.y,
+            rect.height,
+            parent.height,
+            resized.height,
+        );
+        Rect::new(resized.x + x, resized.y + y, width, height)
+    }
+}
+
+/// Children placed one after another in a row or column.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stack {
+    pub axis: Axis,
+    /// Gap between neighbouring children.
+    pub spacing: u32,
+    pub align: Align,
+    pub justify: Justify,
+    /// Space kept free inside the container's edges.
+    pub padding: u32,
+}
+
+/// Children placed row by row into columns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid {
+    /// Width of each column. Columns of width 0 share the space left over
+    /// by the others equally.
+    pub columns: Vec<u32>,
+    pub column_spacing: u32,
+    pub row_spacing: u32,
+    pub align: Align,
+    pub padding: u32,
+}
+
+/// How a container computes the bounds of its children.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Layout {
+    Stack(Stack),
+    Grid(Grid),
+}
+
+impl Layout {
+    /// The smallest size that fits children of the given sizes.
+    pub fn measure(&self, sizes: &[(u32, u32)]) -> (u32, u32) {
+        let (width, height) = match self {
+            Layout::Stack(stack) => {
+                let gaps = stack.spacing * sizes.len().saturating_sub(1) as u32;
+                let along = |(w, h): (u32, u32)| match stack.axis {
+                    Axis::Horizontal => (w, h),
+                    Axis::Vertical => (h, w),
+                };
+                let main = sizes.iter().map(|&s| along(s).0).sum::<u32>() + gaps;
+                let cross = sizes.iter().map(|&s| along(s).1).max().unwrap_or(0);
+                along((main, cross))
+            }
+            Layout::Grid(grid) => {
+                let widths = grid.natural_widths(sizes);
+                let rows = grid.row_heights(sizes);
+                let column_gaps = grid.column_spacing * widths.len().saturating_sub(1) as u32;
+                let row_gaps = grid.row_spacing * rows.len().saturating_sub(1) as u32;
+                (
+                    widths.iter().sum::<u32>() + column_gaps,
+                    rows.iter().sum::<u32>() + row_gaps,
+                )
+            }
+        };
+        let padding = 2 * self.padding();
+        (width + padding, height + padding)
+    }
+
+    /// Bounds for children of the given sizes inside `bounds`, in order.
+    pub fn arrange(&self, bounds: &Rect, sizes: &[(u32, u32)]) -> Vec<Rect> {
+        let padding = self.padding();
+        let inner = Rect::new(
+            bounds.x + padding as i32,
+            bounds.y + padding as i32,
+            bounds.width.saturating_sub(2 * padding),
+            bounds.height.saturating_sub(2 * padding),
+        );
+        match self {
+            Layout::Stack(stack) => stack.arrange(&inner, sizes),
+            Layout::Grid(grid) => grid.arrange(&inner, sizes),
+        }
+    }
+
+    fn padding(&self) -> u32 {
+        match self {
+            Layout::Stack(stack) => stack.padding,
+            Layout::Grid(grid) => grid.padding,
+        }
+    }
+}
+
+impl Stack {
+    fn arrange(&self, inner: &Rect, sizes: &[(u32, u32)]) -> Vec<Rect> {
+        // Work in (main, cross) coordinates and swap back for rows
+        let horizontal = self.axis == Axis::Horizontal;
+        let swap = |(a, b): (i32, i32)| if horizontal { (a, b) } else { (b, a) };
+        let (main_start, cross_start) = swap((inner.x, inner.y));
+        let (main_length, cross_length) = swap((inner.width as i32, inner.height as i32));
+        let sizes: Vec<(i32, i32)> = sizes
+            .iter()
+            .map(|&(w, h)| swap((w as i32, h as i32)))
+            .collect();
+
+        let count = sizes.len() as i32;
+        let content = sizes.iter().map(|s| s.0).sum::<i32>();
+        let spacing = self.spacing as i32;
+        let free = main_length - content - spacing * (count - 1).max(0);
+        let (mut pen, gap) = match self.justify {
+            Justify::Start => (0, spacing),
+            Justify::Center => (free / 2, spacing),
+            Justify::End => (free, spacing),
+            Justify::SpaceBetween if count > 1 => (0, (main_length - content).max(0) / (count - 1)),
+            Justify::SpaceBetween => (0, spacing),
+        };
+
+        sizes
+            .iter()
+            .map(|&(main, cross)| {
+                let (offset, cross) = align(self.align, cross, cross_length);
+                let (x, y) = swap((main_start + pen, cross_start + offset));
+                let (width, height) = swap((main, cross));
+                pen += main + gap;
+                Rect::new(x, y, width as u32, height as u32)
+            })
+            .collect()
+    }
+}
+
+impl Grid {
+    /// Column widths when every column is as wide as its widest child.
+    fn natural_widths(&self, sizes: &[(u32, u32)]) -> Vec<u32> {
+        let count = self.columns.len().max(1);
+        (0..count)
+            .map(|column| match self.columns.get(column) {
+                Some(&width) if width > 0 => width,
+                _ => sizes
+                    .iter()
+                    .skip(column)
+                    .step_by(count)
+                    .map(|s| s.0)
+                    .max()
+                    .unwrap_or(0),
+            })
+            .collect()
+    }
+
+    fn row_heights(&self, sizes: &[(u32, u32)]) -> Vec<u32> {
+        let count = self.columns.len().max(1);
+        sizes
+            .chunks(count)
+            .map(|row| row.iter().map(|s| s.1).max().unwrap_or(0))
+            .collect()
+    }
+
+    fn arrange(&self, inner: &Rect, sizes: &[(u32, u32)]) -> Vec<Rect> {
+        let count = self.columns.len().max(1);
+        let gaps = self.column_spacing * (count as u32 - 1);
+        let fixed: u32 = self.columns.iter().sum();
+        let flexible = self.columns.iter().filter(|&&w| w == 0).count().max(1) as u32;
+        let share = inner.width.saturating_sub(fixed + gaps) / flexible;
+        let widths: Vec<u32> = (0..count)
+            .map(|column| match self.columns.get(column) {
+                Some(&width) if width > 0 => width,
+                _ => share,
+            })
+            .collect();
+
+        let mut rects = Vec::with_capacity(sizes.len());
+        let mut y = inner.y;
+        for (row, height) in sizes.chunks(count).zip(self.row_heights(sizes)) {
+            let mut x = inner.x;
+            for (&(w, h), &cell_width) in row.iter().zip(&widths) {
+                let (dx, width) = align(self.align, w as i32, cell_width as i32);
+                let (dy, height) = align(self.align, h as i32, height as i32);
+                rects.push(Rect::new(x + dx, y + dy, width as u32, height as u32));
+                x += (cell_width + self.column_spacing) as i32;
+            }
+            y += (height + self.row_spacing) as i32;
+        }
+        rects
+    }
+}
+
+/// Offset and length of something `size` long placed in `space`.
+fn align(align: Align, size: i32, space: i32) -> (i32, i32) {
+    match align {
+        Align::Start => (0, size),
+        Align::Center => ((space - size) / 2, size),
+        Align::End => (space - size, size),
+        Align::Stretch => (0, space),
+    }
+}
+
+/// Offset and length of something at `offset` in a space growing from
+/// `old` to `new`, following the space's edges as `align` says.
+fn follow(align: Align, offset: i32, size: u32, old: u32, new: u32) -> (i32, u32) {
+    let grow = new as i32 - old as i32;
+    match align {
+        Align::Start => (offset, size),
+        Align::Center => (offset + grow / 2, size),
+        Align::End => (offset + grow, size),
+        Align::Stretch => (offset, (size as i32 + grow).max(0) as u32),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stack_places_children_along_its_axis() {
+        let column = Layout::Stack(Stack {
+            spacing: 4,
+            align: Align::Center,
+            padding: 10,
+            ..Stack::default()
+        });
+        let sizes = [(20, 10), (40, 20)];
+        assert_eq!(column.measure(&sizes), (60, 54));
+
+        let rects = column.arrange(&Rect::new(0, 0, 100, 100), &sizes);
+        assert_eq!(
+            rects,
+            [Rect::new(40, 10, 20, 10), Rect::new(30, 24, 40, 20)]
+        );
+
+        let row = Layout::Stack(Stack {
+            axis: Axis::Horizontal,
+            align: Align::Stretch,
+            justify: Justify::SpaceBetween,
+            ..Stack::default()
+        });
+        let rects = row.arrange(&Rect::new(0, 0, 100, 30), &sizes);
+        assert_eq!(rects, [Rect::new(0, 0, 20, 30), Rect::new(60, 0, 40, 30)]);
+    }
+
+    #[test]
+    fn test_anchors_follow_parent_edges() {
+        let parent = Rect::new(0, 0, 100, 100);
+        let resized = Rect::new(10, 0, 140, 80);
+        let rect = Rect::new(70, 10, 20, 20);
+
+        let corner = Anchor {
+            x: Align::End,
+            y: Align::End,
+        };
+        assert_eq!(
+            corner.place(&rect, &parent, &resized),
+            Rect::new(120, -10, 20, 20)
+        );
+
+        let bar = Anchor {
+            x: Align::Stretch,
+            y: Align::Center,
+        };
+        assert_eq!(
+            bar.place(&rect, &parent, &resized),
+            Rect::new(80, 0, 60, 20)
+        );
+
+        // Shrinking past a stretched node collapses it rather than underflowing
+        let tiny = Rect::new(0, 0, 10, 10);
+        assert_eq!(bar.place(&rect, &parent, &tiny).width, 0);
+    }
+
+    #[test]
+    fn test_grid_shares_space_between_flexible_columns() {
+        let grid = Layout::Grid(Grid {
+            columns: vec![30, 0],
+            column_spacing: 10,
+            row_spacing: 2,
+            align: Align::Stretch,
+            padding: 0,
+        });
+        let sizes = [(20, 10), (50, 16), (25, 12)];
+        assert_eq!(grid.measure(&sizes), (90, 30));
+
+        let rects = grid.arrange(&Rect::new(0, 0, 100, 50), &sizes);
+        assert_eq!(
+            rects,
+            [
+                Rect::new(0, 0, 30, 16),
+                Rect::new(40, 0, 60, 16),
+                Rect::new(0, 18, 30, 12)
+            ]
+        );
+    }
+}
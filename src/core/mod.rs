@@ -1,5 +1,7 @@
 mod action;
 mod app;
+mod hit;
+mod layout;
 mod node;
 mod rect;
 mod store;
@@ -8,7 +10,9 @@ mod view;
 mod widget;
 
 pub use action::{Action, ActionDispatcher, ActionError, ActionHandler, Services};
-pub use app::{App, AppRunner};
+pub use app::{App, AppRunner, UiScale, WindowRequest};
+pub use hit::{HitMask, HitRegion};
+pub use layout::{Align, Anchor, Axis, Grid, Justify, Layout, Stack};
 pub use node::{Node, NodeId};
 pub use rect::Rect;
 pub use store::{Store, Value};
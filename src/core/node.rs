@@ -1,4 +1,4 @@
-use crate::core::{Rect, Widget};
+use crate::core::{Anchor, HitRegion, Layout, Rect, Widget};
 
 /// A handle to a node in the UI tree.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -10,12 +10,30 @@ impl NodeId {
     }
 }
 
+/// A node's anchor, with the bounds it and its parent had when it was set.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Anchoring {
+    pub(crate) anchor: Anchor,
+    pub(crate) bounds: Rect,
+    pub(crate) parent: Rect,
+}
+
 /// A node in the UI tree.
 pub struct Node {
     pub(crate) widget: Box<dyn Widget>,
     pub(crate) children: Vec<NodeId>,
     pub(crate) parent: Option<NodeId>,
     pub(crate) bounds: Rect,
+    /// Where within its bounds the node is hit (anywhere if `None`).
+    pub(crate) hit_region: Option<HitRegion>,
+    /// How the node places its children, if it does.
+    pub(crate) layout: Option<Layout>,
+    /// How the node follows its parent when the parent is resized.
+    pub(crate) anchor: Option<Anchoring>,
+    /// Hidden nodes and everything inside them are neither drawn nor hit.
+    pub(crate) visible: bool,
+    /// Whether children are cut off at the node's bounds.
+    pub(crate) clip: bool,
 }
 
 impl Node {
@@ -25,6 +43,11 @@ impl Node {
             children: Vec::new(),
             parent: None,
             bounds: Rect::default(),
+            hit_region: None,
+            layout: None,
+            anchor: None,
+            visible: true,
+            clip: false,
         }
     }
 
@@ -32,6 +55,26 @@ impl Node {
         &self.bounds
     }
 
+    pub fn hit_region(&self) -> Option<&HitRegion> {
+        self.hit_region.as_ref()
+    }
+
+    pub fn layout(&self) -> Option<&Layout> {
+        self.layout.as_ref()
+    }
+
+    pub fn anchor(&self) -> Option<Anchor> {
+        self.anchor.map(|anchoring| anchoring.anchor)
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn clips(&self) -> bool {
+        self.clip
+    }
+
     pub fn children(&self) -> &[NodeId] {
         &self.children
     }
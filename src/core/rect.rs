@@ -24,6 +24,11 @@ impl Rect {
             && py < self.y + self.height as i32
     }
 
+    /// The same rectangle moved by (`dx`, `dy`).
+    pub fn offset(&self, dx: i32, dy: i32) -> Rect {
+        Rect::new(self.x + dx, self.y + dy, self.width, self.height)
+    }
+
     pub fn right(&self) -> i32 {
         self.x + self.width as i32
     }
@@ -31,4 +36,38 @@ impl Rect {
     pub fn bottom(&self) -> i32 {
         self.y + self.height as i32
     }
+
+    /// Check if the rectangle has no area.
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Check if two rectangles overlap.
+    pub fn intersects(&self, other: &Rect) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    /// The overlapping area of two rectangles (empty if they don't overlap).
+    pub fn intersection(&self, other: &Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        Rect::new(x, y, (right - x).max(0) as u32, (bottom - y).max(0) as u32)
+    }
+
+    /// The smallest rectangle containing both rectangles.
+    pub fn union(&self, other: &Rect) -> Rect {
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+        Rect::new(x, y, (right - x) as u32, (bottom - y) as u32)
+    }
 }
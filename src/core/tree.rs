@@ -1,4 +1,8 @@
-use crate::core::{Node, NodeId, Rect, Widget, WidgetState};
+use std::collections::HashMap;
+use std::time::Instant;
+
+use crate::core::node::Anchoring;
+use crate::core::{Anchor, HitRegion, Layout, Node, NodeId, Rect, Widget, WidgetState};
 use crate::graphics::Canvas;
 
 /// The UI tree that owns all nodes in an arena.
@@ -10,8 +14,15 @@ pub struct UiTree {
     pressed: Option<NodeId>,
     focused: Option<NodeId>,
     captured: Option<NodeId>,
+    /// Regions that need repainting since the last `take_damage`.
+    damage: Vec<Rect>,
+    /// Nodes registered under a name (skin part ids).
+    names: HashMap<String, NodeId>,
 }
 
+/// Above this many damaged rects they are collapsed into their union.
+const MAX_DAMAGE_RECTS: usize = 16;
+
 impl UiTree {
     pub fn new() -> Self {
         Self {
@@ -22,6 +33,8 @@ impl UiTree {
             pressed: None,
             focused: None,
             captured: None,
+            damage: Vec::new(),
+            names: HashMap::new(),
         }
     }
 
@@ -52,6 +65,8 @@ impl UiTree {
 
     /// Remove a node and all its children from the tree.
     pub fn remove(&mut self, id: NodeId) {
+        self.invalidate(id);
+
         // First collect children to remove
         let children: Vec<NodeId> = self
             .get(id)
@@ -88,6 +103,7 @@ impl UiTree {
         if self.captured == Some(id) {
             self.captured = None;
         }
+        self.names.retain(|_, named| *named != id);
 
         // Free the slot
         if let Some(slot) = self.nodes.get_mut(id.index()) {
@@ -96,6 +112,16 @@ impl UiTree {
         }
     }
 
+    /// Register `id` under `name` so it can be found with `find_by_name`.
+    pub fn set_name(&mut self, id: NodeId, name: impl Into<String>) {
+        self.names.insert(name.into(), id);
+    }
+
+    /// Look up a node registered with `set_name`.
+    pub fn find_by_name(&self, name: &str) -> Option<NodeId> {
+        self.names.get(name).copied()
+    }
+
     fn allocate_slot(&mut self, node: Node) -> NodeId {
         if let Some(index) = self.free_list.pop() {
             self.nodes[index] = Some(node);
@@ -111,10 +137,32 @@ impl UiTree {
         self.nodes.get(id.index()).and_then(|n| n.as_ref())
     }
 
+    /// Get a node for modification. The node is marked as damaged, since
+    /// the caller may change what its widget draws.
     pub fn get_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.invalidate(id);
+        self.get_mut_untracked(id)
+    }
+
+    /// Get a node for modification without marking it as damaged. Call
+    /// `invalidate` if its widget ends up drawing something different.
+    pub fn get_mut_untracked(&mut self, id: NodeId) -> Option<&mut Node> {
         self.nodes.get_mut(id.index()).and_then(|n| n.as_mut())
     }
 
+    /// IDs of nodes whose widget is a `T` matching `pred`.
+    /// Use this to find widgets that need updating before calling `get_mut`,
+    /// so untouched nodes aren't repainted.
+    pub fn find_widgets<T: 'static>(&self, pred: impl Fn(&T) -> bool) -> Vec<NodeId> {
+        self.iter_node_ids()
+            .filter(|&id| {
+                self.get(id)
+                    .and_then(|node| node.widget.as_any().downcast_ref::<T>())
+                    .is_some_and(&pred)
+            })
+            .collect()
+    }
+
     pub fn root(&self) -> Option<NodeId> {
         self.root
     }
@@ -129,6 +177,207 @@ impl UiTree {
             node.bounds = bounds;
             node.widget.set_bounds(bounds);
         }
+        // Repaint both the old area (via get_mut) and the new one
+        self.invalidate_rect(bounds);
+    }
+
+    /// Move a node and everything inside it so its top-left corner is at (x, y).
+    pub fn move_to(&mut self, id: NodeId, x: i32, y: i32) {
+        let Some(node) = self.nodes.get_mut(id.index()).and_then(|n| n.as_mut()) else {
+            return;
+        };
+        let (dx, dy) = (x - node.bounds.x, y - node.bounds.y);
+        // Its anchor keeps the distances from the new position from now on
+        if let Some(anchoring) = &mut node.anchor {
+            anchoring.bounds = anchoring.bounds.offset(dx, dy);
+        }
+        self.translate(id, dx, dy);
+    }
+
+    fn translate(&mut self, id: NodeId, dx: i32, dy: i32) {
+        let Some(node) = self.get(id) else {
+            return;
+        };
+        let (bounds, children) = (node.bounds.offset(dx, dy), node.children.clone());
+        self.set_bounds(id, bounds);
+        for child in children {
+            self.translate(child, dx, dy);
+        }
+    }
+
+    /// Show or hide a node together with everything inside it.
+    pub fn set_visible(&mut self, id: NodeId, visible: bool) {
+        if self.get(id).is_some_and(|node| node.visible != visible) {
+            self.invalidate_subtree(id);
+            if let Some(node) = self.nodes.get_mut(id.index()).and_then(|n| n.as_mut()) {
+                node.visible = visible;
+            }
+        }
+    }
+
+    /// Cut a node's children off at its bounds, or let them draw outside.
+    pub fn set_clip(&mut self, id: NodeId, clip: bool) {
+        if self.get(id).is_some_and(|node| node.clip != clip) {
+            self.invalidate_subtree(id);
+            if let Some(node) = self.nodes.get_mut(id.index()).and_then(|n| n.as_mut()) {
+                node.clip = clip;
+            }
+        }
+    }
+
+    /// Whether the node and all of its ancestors are visible.
+    pub fn is_shown(&self, id: NodeId) -> bool {
+        let mut current = Some(id);
+        while let Some(node) = current.and_then(|id| self.get(id)) {
+            if !node.visible {
+                return false;
+            }
+            current = node.parent;
+        }
+        true
+    }
+
+    /// Restrict where within its bounds a node is hit. Points outside the
+    /// region fall through to whatever is underneath.
+    pub fn set_hit_region(&mut self, id: NodeId, region: Option<HitRegion>) {
+        // Nothing changes on screen, so this doesn't damage the node
+        if let Some(node) = self.nodes.get_mut(id.index()).and_then(|n| n.as_mut()) {
+            node.hit_region = region;
+        }
+    }
+
+    /// Let a node place its children. Takes effect on the next `apply_layout`.
+    pub fn set_layout(&mut self, id: NodeId, layout: Option<Layout>) {
+        if let Some(node) = self.nodes.get_mut(id.index()).and_then(|n| n.as_mut()) {
+            node.layout = layout;
+        }
+    }
+
+    /// Anchor a node to its parent's edges. The distances it keeps are
+    /// measured from both nodes' current bounds. Takes effect on the next
+    /// `apply_layout`, and is ignored while the parent has a layout.
+    pub fn set_anchor(&mut self, id: NodeId, anchor: Option<Anchor>) {
+        let parent = self
+            .get(id)
+            .and_then(|node| node.parent)
+            .and_then(|p| self.get(p));
+        let parent = parent.map(|parent| parent.bounds).unwrap_or_default();
+        if let Some(node) = self.nodes.get_mut(id.index()).and_then(|n| n.as_mut()) {
+            node.anchor = anchor.map(|anchor| Anchoring {
+                anchor,
+                bounds: node.bounds,
+                parent,
+            });
+        }
+    }
+
+    /// Position the children of `id` and of every node below it that has a
+    /// layout or anchored children, parents first. Children keep their
+    /// current sizes unless their layout or anchor stretches them.
+    pub fn apply_layout(&mut self, id: NodeId) {
+        let Some(node) = self.get(id) else {
+            return;
+        };
+        let children = node.children.clone();
+        let bounds = node.bounds;
+        if let Some(layout) = &node.layout {
+            let sizes: Vec<(u32, u32)> = children
+                .iter()
+                .filter_map(|&child| self.get(child))
+                .map(|child| (child.bounds.width, child.bounds.height))
+                .collect();
+            let rects = layout.arrange(&bounds, &sizes);
+            for (&child, rect) in children.iter().zip(rects) {
+                self.set_bounds(child, rect);
+            }
+        } else {
+            for &child in &children {
+                let Some(child_node) = self.get(child) else {
+                    continue;
+                };
+                if let Some(Anchoring {
+                    anchor,
+                    bounds: rect,
+                    parent,
+                }) = child_node.anchor
+                {
+                    let placed = anchor.place(&rect, &parent, &bounds);
+                    // Unmoved children aren't damaged
+                    if child_node.bounds != placed {
+                        self.set_bounds(child, placed);
+                    }
+                }
+            }
+        }
+        for child in children {
+            self.apply_layout(child);
+        }
+    }
+
+    // Damage tracking
+
+    /// Mark a node's area as needing a repaint.
+    pub fn invalidate(&mut self, id: NodeId) {
+        if let Some(bounds) = self.get(id).map(|node| node.bounds) {
+            self.invalidate_rect(bounds);
+        }
+    }
+
+    /// Mark a node's area and that of everything inside it as needing a
+    /// repaint, since children may extend outside their parent.
+    fn invalidate_subtree(&mut self, id: NodeId) {
+        self.invalidate(id);
+        let children = self
+            .get(id)
+            .map(|node| node.children.clone())
+            .unwrap_or_default();
+        for child in children {
+            self.invalidate_subtree(child);
+        }
+    }
+
+    /// Mark an area as needing a repaint.
+    pub fn invalidate_rect(&mut self, rect: Rect) {
+        if rect.is_empty() {
+            return;
+        }
+        // Merge into an overlapping rect to keep the list short
+        if let Some(existing) = self.damage.iter_mut().find(|r| r.intersects(&rect)) {
+            *existing = existing.union(&rect);
+        } else {
+            self.damage.push(rect);
+        }
+        if self.damage.len() > MAX_DAMAGE_RECTS {
+            let union = self
+                .damage
+                .iter()
+                .fold(Rect::default(), |acc, r| acc.union(r));
+            self.damage = vec![union];
+        }
+    }
+
+    /// Mark the whole tree as needing a repaint.
+    pub fn invalidate_all(&mut self) {
+        if let Some(root) = self.root {
+            self.invalidate(root);
+        }
+    }
+
+    /// Take the regions damaged since the last call.
+    /// Shown nodes whose animation is due for its next frame are included.
+    pub fn take_damage(&mut self) -> Vec<Rect> {
+        let now = Instant::now();
+        let animating: Vec<Rect> = self
+            .iter_node_ids()
+            .filter(|&id| self.is_shown(id))
+            .filter_map(|id| self.get(id))
+            .filter(|node| node.widget.next_frame().is_some_and(|due| due <= now))
+            .map(|node| node.bounds)
+            .collect();
+        for rect in animating {
+            self.invalidate_rect(rect);
+        }
+        std::mem::take(&mut self.damage)
     }
 
     // State accessors
@@ -138,6 +387,9 @@ impl UiTree {
     }
 
     pub fn set_hovered(&mut self, id: Option<NodeId>) {
+        if self.hovered != id {
+            self.invalidate_state_change(self.hovered, id);
+        }
         self.hovered = id;
     }
 
@@ -146,6 +398,9 @@ impl UiTree {
     }
 
     pub fn set_pressed(&mut self, id: Option<NodeId>) {
+        if self.pressed != id {
+            self.invalidate_state_change(self.pressed, id);
+        }
         self.pressed = id;
     }
 
@@ -154,6 +409,9 @@ impl UiTree {
     }
 
     pub fn set_focused(&mut self, id: Option<NodeId>) {
+        if self.focused != id {
+            self.invalidate_state_change(self.focused, id);
+        }
         self.focused = id;
     }
 
@@ -165,6 +423,13 @@ impl UiTree {
         self.captured = id;
     }
 
+    /// Damage the nodes losing and gaining a hover/press/focus state.
+    fn invalidate_state_change(&mut self, old: Option<NodeId>, new: Option<NodeId>) {
+        for id in [old, new].into_iter().flatten() {
+            self.invalidate(id);
+        }
+    }
+
     /// Iterate over all valid node IDs.
     pub fn iter_node_ids(&self) -> impl Iterator<Item = NodeId> + '_ {
         self.nodes
@@ -181,8 +446,10 @@ impl UiTree {
 
     fn hit_test_node(&self, id: NodeId, x: i32, y: i32) -> Option<NodeId> {
         let node = self.get(id)?;
+        let inside = node.bounds.contains(x, y);
 
-        if !node.bounds.contains(x, y) {
+        // Children may be drawn outside their parent unless it clips
+        if !node.visible || (node.clip && !inside) {
             return None;
         }
 
@@ -193,8 +460,20 @@ impl UiTree {
             }
         }
 
-        // No child hit, this node is the target
-        Some(id)
+        // No child hit, this node is the target if its hit region allows
+        let in_region = match &node.hit_region {
+            Some(region) => region.contains(&node.bounds, x, y),
+            None => true,
+        };
+        (inside && in_region).then_some(id)
+    }
+
+    /// Earliest time any shown widget needs to be redrawn without input.
+    pub fn next_frame(&self) -> Option<Instant> {
+        self.iter_node_ids()
+            .filter(|&id| self.is_shown(id))
+            .filter_map(|id| self.get(id)?.widget.next_frame())
+            .min()
     }
 
     /// Draw the entire tree to the canvas.
@@ -204,10 +483,26 @@ impl UiTree {
         }
     }
 
+    /// Repaint only the nodes overlapping `region`, clipped to it.
+    pub fn draw_region(&self, canvas: &mut Canvas, region: &Rect) {
+        canvas.push_clip(*region);
+        if let Some(root) = self.root {
+            self.draw_node_in(root, canvas, Some(region));
+        }
+        canvas.pop_clip();
+    }
+
     fn draw_node(&self, id: NodeId, canvas: &mut Canvas) {
+        self.draw_node_in(id, canvas, None);
+    }
+
+    fn draw_node_in(&self, id: NodeId, canvas: &mut Canvas, region: Option<&Rect>) {
         let Some(node) = self.get(id) else {
             return;
         };
+        if !node.visible {
+            return;
+        }
 
         let state = WidgetState {
             hovered: self.hovered == Some(id),
@@ -216,12 +511,19 @@ impl UiTree {
         };
 
         let bounds = node.bounds;
-        node.widget.draw(canvas, &bounds, state);
+        if region.is_none_or(|region| region.intersects(&bounds)) {
+            node.widget.draw(canvas, &bounds, state);
+        }
 
-        // Draw children
-        let children: Vec<NodeId> = node.children.clone();
-        for child_id in children {
-            self.draw_node(child_id, canvas);
+        // Draw children (they may extend outside their parent unless it clips)
+        if node.clip {
+            canvas.push_clip(bounds);
+        }
+        for &child_id in &node.children {
+            self.draw_node_in(child_id, canvas, region);
+        }
+        if node.clip {
+            canvas.pop_clip();
         }
     }
 }
@@ -245,4 +547,213 @@ impl crate::core::View for UiTree {
     fn draw(&self, canvas: &mut Canvas) {
         UiTree::draw(self, canvas);
     }
+
+    fn draw_region(&self, canvas: &mut Canvas, region: &Rect) {
+        UiTree::draw_region(self, canvas, region);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::Container;
+    use std::any::Any;
+    use std::time::Duration;
+
+    /// A widget whose next frame is due at a fixed time.
+    struct Animated(Instant);
+
+    impl Widget for Animated {
+        fn draw(&self, _canvas: &mut Canvas, _bounds: &Rect, _state: WidgetState) {}
+
+        fn next_frame(&self) -> Option<Instant> {
+            Some(self.0)
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    #[test]
+    fn test_state_changes_damage_only_affected_nodes() {
+        let mut tree = UiTree::new();
+        let root = tree.add(Container::transparent(100, 100), None);
+        let a = tree.add(Container::transparent(10, 10), Some(root));
+        let b = tree.add(Container::transparent(10, 10), Some(root));
+        tree.set_bounds(root, Rect::new(0, 0, 100, 100));
+        tree.set_bounds(a, Rect::new(0, 0, 10, 10));
+        tree.set_bounds(b, Rect::new(50, 50, 10, 10));
+        tree.take_damage();
+
+        tree.set_hovered(Some(a));
+        assert_eq!(tree.take_damage(), vec![Rect::new(0, 0, 10, 10)]);
+
+        tree.set_hovered(Some(a));
+        assert!(tree.take_damage().is_empty());
+
+        tree.set_hovered(Some(b));
+        assert_eq!(
+            tree.take_damage(),
+            vec![Rect::new(0, 0, 10, 10), Rect::new(50, 50, 10, 10)]
+        );
+
+        // Looking at a node doesn't damage it, changing it does
+        tree.get_mut_untracked(a);
+        assert!(tree.take_damage().is_empty());
+        tree.get_mut(a);
+        assert_eq!(tree.take_damage(), vec![Rect::new(0, 0, 10, 10)]);
+    }
+
+    #[test]
+    fn test_animations_damage_only_when_a_frame_is_due() {
+        let mut tree = UiTree::new();
+        let root = tree.add(Container::transparent(100, 100), None);
+        let later = Instant::now() + Duration::from_secs(60);
+        let waiting = tree.add(Animated(later), Some(root));
+        let due = tree.add(Animated(Instant::now()), Some(root));
+        tree.set_bounds(root, Rect::new(0, 0, 100, 100));
+        tree.set_bounds(waiting, Rect::new(0, 0, 10, 10));
+        tree.set_bounds(due, Rect::new(50, 50, 10, 10));
+        tree.take_damage();
+
+        assert_eq!(tree.take_damage(), vec![Rect::new(50, 50, 10, 10)]);
+    }
+
+    #[test]
+    fn test_clicks_outside_hit_region_fall_through() {
+        let mut tree = UiTree::new();
+        let root = tree.add(Container::transparent(100, 100), None);
+        let below = tree.add(Container::transparent(20, 20), Some(root));
+        let round = tree.add(Container::transparent(20, 20), Some(root));
+        tree.set_bounds(root, Rect::new(0, 0, 100, 100));
+        tree.set_bounds(below, Rect::new(0, 0, 20, 20));
+        tree.set_bounds(round, Rect::new(0, 0, 20, 20));
+        tree.set_hit_region(round, Some(HitRegion::Ellipse));
+
+        assert_eq!(tree.hit_test(10, 10), Some(round));
+        assert_eq!(tree.hit_test(0, 0), Some(below));
+        assert_eq!(tree.hit_test(50, 50), Some(root));
+    }
+
+    #[test]
+    fn test_nested_layouts_place_children_top_down() {
+        use crate::core::{Align, Axis, Layout, Stack};
+
+        let mut tree = UiTree::new();
+        let root = tree.add(Container::transparent(100, 100), None);
+        let row = tree.add(Container::transparent(0, 0), Some(root));
+        let a = tree.add(Container::transparent(10, 10), Some(row));
+        let b = tree.add(Container::transparent(10, 10), Some(row));
+        tree.set_bounds(root, Rect::new(0, 0, 100, 100));
+        tree.set_bounds(row, Rect::new(0, 0, 20, 10));
+        tree.set_bounds(a, Rect::new(0, 0, 10, 10));
+        tree.set_bounds(b, Rect::new(0, 0, 10, 10));
+
+        // The column stretches the row, then the row places its children
+        let column = Stack {
+            align: Align::Stretch,
+            padding: 5,
+            ..Stack::default()
+        };
+        let row_stack = Stack {
+            axis: Axis::Horizontal,
+            spacing: 2,
+            ..Stack::default()
+        };
+        tree.set_layout(root, Some(Layout::Stack(column)));
+        tree.set_layout(row, Some(Layout::Stack(row_stack)));
+        tree.apply_layout(root);
+
+        assert_eq!(tree.get(row).unwrap().bounds(), &Rect::new(5, 5, 90, 10));
+        assert_eq!(tree.get(b).unwrap().bounds(), &Rect::new(17, 5, 10, 10));
+    }
+
+    #[test]
+    fn test_anchored_children_follow_a_resized_parent() {
+        use crate::core::Align;
+
+        let mut tree = UiTree::new();
+        let root = tree.add(Container::transparent(100, 100), None);
+        let panel = tree.add(Container::transparent(80, 80), Some(root));
+        let button = tree.add(Container::transparent(20, 10), Some(panel));
+        let fixed = tree.add(Container::transparent(10, 10), Some(panel));
+        tree.set_bounds(root, Rect::new(0, 0, 100, 100));
+        tree.set_bounds(panel, Rect::new(10, 10, 80, 80));
+        tree.set_bounds(button, Rect::new(60, 70, 20, 10));
+        tree.set_bounds(fixed, Rect::new(10, 10, 10, 10));
+        let stretch = Anchor {
+            x: Align::Stretch,
+            y: Align::Stretch,
+        };
+        tree.set_anchor(panel, Some(stretch));
+        tree.set_anchor(
+            button,
+            Some(Anchor {
+                x: Align::End,
+                y: Align::End,
+            }),
+        );
+
+        tree.set_bounds(root, Rect::new(0, 0, 200, 150));
+        tree.apply_layout(root);
+
+        assert_eq!(
+            tree.get(panel).unwrap().bounds(),
+            &Rect::new(10, 10, 180, 130)
+        );
+        assert_eq!(
+            tree.get(button).unwrap().bounds(),
+            &Rect::new(160, 120, 20, 10)
+        );
+        assert_eq!(
+            tree.get(fixed).unwrap().bounds(),
+            &Rect::new(10, 10, 10, 10)
+        );
+    }
+
+    #[test]
+    fn test_groups_move_and_hide_as_a_unit() {
+        let mut tree = UiTree::new();
+        let root = tree.add(Container::transparent(100, 100), None);
+        let group = tree.add(Container::transparent(40, 40), Some(root));
+        let inner = tree.add(Container::transparent(10, 10), Some(group));
+        tree.set_bounds(root, Rect::new(0, 0, 100, 100));
+        tree.set_bounds(group, Rect::new(10, 10, 40, 40));
+        tree.set_bounds(inner, Rect::new(20, 20, 10, 10));
+
+        tree.move_to(group, 50, 30);
+        assert_eq!(
+            tree.get(inner).unwrap().bounds(),
+            &Rect::new(60, 40, 10, 10)
+        );
+        assert_eq!(tree.hit_test(65, 45), Some(inner));
+
+        tree.take_damage();
+        tree.set_visible(group, false);
+        assert_eq!(tree.take_damage(), vec![Rect::new(50, 30, 40, 40)]);
+        assert!(!tree.is_shown(inner));
+        assert_eq!(tree.hit_test(65, 45), Some(root));
+    }
+
+    #[test]
+    fn test_children_outside_a_parent_are_hit_unless_it_clips() {
+        let mut tree = UiTree::new();
+        let root = tree.add(Container::transparent(100, 100), None);
+        let group = tree.add(Container::transparent(20, 20), Some(root));
+        let outside = tree.add(Container::transparent(10, 10), Some(group));
+        tree.set_bounds(root, Rect::new(0, 0, 100, 100));
+        tree.set_bounds(group, Rect::new(10, 10, 20, 20));
+        tree.set_bounds(outside, Rect::new(50, 50, 10, 10));
+
+        assert_eq!(tree.hit_test(55, 55), Some(outside));
+        assert_eq!(tree.hit_test(40, 40), Some(root));
+
+        tree.set_clip(group, true);
+        assert_eq!(tree.hit_test(55, 55), Some(root));
+    }
 }
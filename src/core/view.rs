@@ -1,3 +1,4 @@
+use crate::core::Rect;
 use crate::graphics::Canvas;
 
 /// The core trait for anything that can be drawn.
@@ -8,4 +9,12 @@ pub trait View {
 
     /// Draw this view to the canvas.
     fn draw(&self, canvas: &mut Canvas);
+
+    /// Repaint only `region` of the view. The default redraws everything
+    /// clipped to the region; views that can skip work should override it.
+    fn draw_region(&self, canvas: &mut Canvas, region: &Rect) {
+        canvas.push_clip(*region);
+        self.draw(canvas);
+        canvas.pop_clip();
+    }
 }
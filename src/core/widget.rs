@@ -1,4 +1,5 @@
 use std::any::Any;
+use std::time::Instant;
 
 use crate::core::Rect;
 use crate::graphics::Canvas;
@@ -58,6 +59,12 @@ pub trait Widget {
     /// Widgets that need to track their bounds for hit testing can override this.
     fn set_bounds(&mut self, _bounds: Rect) {}
 
+    /// Returns when this widget next changes on its own (e.g. the next frame of
+    /// an animation). `None` means it only changes in response to events.
+    fn next_frame(&self) -> Option<Instant> {
+        None
+    }
+
     /// Return self as Any for downcasting.
     fn as_any(&self) -> &dyn Any;
 
@@ -1,3 +1,5 @@
+use image::RgbaImage;
+
 use crate::core::Rect;
 
 /// A drawing surface that Views render to.
@@ -7,6 +9,8 @@ pub struct Canvas<'a> {
     width: u32,
     height: u32,
     clip_rect: Option<Rect>,
+    /// Clip rects saved by `push_clip`.
+    clip_stack: Vec<Option<Rect>>,
 }
 
 impl<'a> Canvas<'a> {
@@ -16,6 +20,7 @@ impl<'a> Canvas<'a> {
             width,
             height,
             clip_rect: None,
+            clip_stack: Vec::new(),
         }
     }
 
@@ -32,6 +37,21 @@ impl<'a> Canvas<'a> {
         self.clip_rect = rect;
     }
 
+    /// Narrow the clip rect to its intersection with `rect`.
+    /// Restore the previous clip with `pop_clip`.
+    pub fn push_clip(&mut self, rect: Rect) {
+        self.clip_stack.push(self.clip_rect);
+        self.clip_rect = Some(match self.clip_rect {
+            Some(current) => current.intersection(&rect),
+            None => rect,
+        });
+    }
+
+    /// Restore the clip rect saved by the matching `push_clip`.
+    pub fn pop_clip(&mut self) {
+        self.clip_rect = self.clip_stack.pop().flatten();
+    }
+
     /// Get the current clipping rectangle.
     pub fn clip_rect(&self) -> Option<&Rect> {
         self.clip_rect.as_ref()
@@ -57,12 +77,78 @@ impl<'a> Canvas<'a> {
         }
     }
 
+    /// Read a pixel, or `None` outside the canvas.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<u32> {
+        if x < self.width && y < self.height {
+            Some(self.buffer[(y * self.width + x) as usize])
+        } else {
+            None
+        }
+    }
+
     /// Set a pixel using RGB components.
     pub fn set_pixel_rgb(&mut self, x: u32, y: u32, r: u8, g: u8, b: u8) {
         let color = (r as u32) << 16 | (g as u32) << 8 | (b as u32);
         self.set_pixel(x, y, color);
     }
 
+    /// Composite a color over the existing pixel using source-over blending.
+    /// An alpha of 255 replaces the pixel, 0 leaves it untouched.
+    pub fn blend_pixel(&mut self, x: u32, y: u32, color: u32, alpha: u8) {
+        if alpha == 0 || x >= self.width || y >= self.height || self.is_clipped(x, y) {
+            return;
+        }
+
+        let index = (y * self.width + x) as usize;
+        if alpha == 255 {
+            self.buffer[index] = color;
+            return;
+        }
+
+        self.buffer[index] = blend(self.buffer[index], color, alpha);
+    }
+
+    /// Composite a pixel using RGBA components.
+    pub fn blend_pixel_rgba(&mut self, x: u32, y: u32, r: u8, g: u8, b: u8, a: u8) {
+        let color = (r as u32) << 16 | (g as u32) << 8 | (b as u32);
+        self.blend_pixel(x, y, color, a);
+    }
+
+    /// Composite an RGBA image with its top-left corner at (x, y).
+    /// Pixels outside `clip` (if given), the canvas clip rect or the canvas are skipped.
+    pub fn draw_image(&mut self, image: &RgbaImage, x: i32, y: i32, clip: Option<&Rect>) {
+        // Only walk the part of the image that can be drawn
+        let mut visible = Rect::new(x, y, image.width(), image.height()).intersection(&Rect::new(
+            0,
+            0,
+            self.width,
+            self.height,
+        ));
+        for clip in clip.into_iter().chain(self.clip_rect.as_ref()) {
+            visible = visible.intersection(clip);
+        }
+        if visible.is_empty() {
+            return;
+        }
+
+        for py in visible.y..visible.bottom() {
+            let row = (py as u32 * self.width) as usize;
+            for px in visible.x..visible.right() {
+                let [r, g, b, a] = image.get_pixel((px - x) as u32, (py - y) as u32).0;
+                if a == 0 {
+                    continue;
+                }
+                let color = (r as u32) << 16 | (g as u32) << 8 | (b as u32);
+                let index = row + px as usize;
+                self.buffer[index] = if a == 255 {
+                    color
+                } else {
+                    blend(self.buffer[index], color, a)
+                };
+            }
+        }
+    }
+
     /// Fill the entire canvas with a color.
     pub fn clear(&mut self, color: u32) {
         self.buffer.fill(color);
@@ -83,3 +169,62 @@ impl<'a> Canvas<'a> {
         }
     }
 }
+
+/// Composite `color` over `dst` with the given opacity (source-over).
+pub fn blend(dst: u32, color: u32, alpha: u8) -> u32 {
+    let a = alpha as u32;
+    let inv = 255 - a;
+    let mix = |shift: u32| {
+        let s = (color >> shift) & 0xFF;
+        let d = (dst >> shift) & 0xFF;
+        ((s * a + d * inv + 127) / 255) << shift
+    };
+    mix(16) | mix(8) | mix(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn test_blend_pixel_source_over() {
+        let mut buffer = vec![0x0000FF; 4];
+        let mut canvas = Canvas::new(&mut buffer, 2, 2);
+
+        canvas.blend_pixel(0, 0, 0xFF0000, 255);
+        canvas.blend_pixel(1, 0, 0xFF0000, 0);
+        canvas.blend_pixel(0, 1, 0xFF0000, 128);
+
+        assert_eq!(buffer[0], 0xFF0000);
+        assert_eq!(buffer[1], 0x0000FF);
+        assert_eq!(buffer[2], 0x80007F);
+    }
+
+    #[test]
+    fn test_draw_image_skips_transparent_pixels() {
+        let mut buffer = vec![0x123456; 4];
+        let mut canvas = Canvas::new(&mut buffer, 2, 2);
+
+        let mut image = RgbaImage::new(2, 1);
+        image.put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        image.put_pixel(1, 0, Rgba([255, 255, 255, 0]));
+        canvas.draw_image(&image, 0, 1, None);
+
+        assert_eq!(buffer, vec![0x123456, 0x123456, 0xFFFFFF, 0x123456]);
+    }
+
+    #[test]
+    fn test_draw_image_clips_to_canvas_and_clip_rects() {
+        let mut buffer = vec![0; 16];
+        let mut canvas = Canvas::new(&mut buffer, 4, 4);
+        canvas.set_clip(Some(Rect::new(0, 0, 4, 3)));
+
+        let image = RgbaImage::from_pixel(3, 3, Rgba([255, 255, 255, 255]));
+        canvas.draw_image(&image, 2, -1, Some(&Rect::new(0, 0, 3, 4)));
+        canvas.draw_image(&image, -5, 0, None);
+
+        let drawn: Vec<usize> = (0..16).filter(|&i| buffer[i] != 0).collect();
+        assert_eq!(drawn, vec![2, 6]);
+    }
+}
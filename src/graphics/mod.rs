@@ -1,13 +1,17 @@
 mod canvas;
 mod image;
 mod renderer;
+mod shapes;
+mod sprite;
 pub mod text;
 
-pub use canvas::Canvas;
+pub use canvas::{blend, Canvas};
 pub use image::Image;
-pub use renderer::Renderer;
+pub use renderer::{OffscreenRenderer, Renderer};
+pub use shapes::{GradientStop, Paint};
+pub use sprite::{NineSlice, Playback, Playhead, SliceMode, Sprite};
 pub use text::{
-    draw_caret, draw_text, draw_text_sized, measure_text,
-    caret_x, caret_x_sized, line_height, line_height_sized,
-    init_font, TextStyle, FontError,
+    caret_positions_sized, caret_x_sized, draw_caret, draw_text_sized, line_height_sized,
+    measure_text, BitmapFont, Font, FontError, FontRegistry, TextEffects, TextGlow, TextOutline,
+    TextShadow, TextStyle,
 };
@@ -1,18 +1,43 @@
 use std::num::NonZeroU32;
 use std::rc::Rc;
 
+use image::RgbaImage;
 use softbuffer::Surface;
 use winit::event_loop::OwnedDisplayHandle;
 use winit::window::Window;
 
-use crate::core::View;
+use crate::core::{HitRegion, Rect, View};
 use crate::graphics::Canvas;
 
+/// Color the frame is cleared to before views draw.
+const CLEAR_COLOR: u32 = 0x000000;
+
+/// Pixels outside a shaped window's region. Zero alpha as well as black, so
+/// compositors that honor the surface's alpha show the desktop through them.
+const TRANSPARENT: u32 = 0x00000000;
+
+/// Alpha bits of the pixels inside a shaped window. Views draw 0x00RRGGBB,
+/// which a surface with an alpha channel would show as fully transparent.
+const OPAQUE: u32 = 0xFF000000;
+
 /// Handles rendering Views to the window surface.
+///
+/// Views are drawn at UI size and every UI pixel is presented as a
+/// `scale` x `scale` block of screen pixels.
 pub struct Renderer {
     surface: Surface<OwnedDisplayHandle, Rc<Window>>,
+    /// Surface size in screen pixels.
     width: u32,
     height: u32,
+    scale: u32,
+    /// Last rendered frame, in UI pixels. Damaged regions are repainted into
+    /// it and copied to the surface, since surface buffers may not keep their
+    /// contents.
+    frame: Vec<u32>,
+    /// Whether `frame` holds a complete frame at the current size.
+    frame_valid: bool,
+    /// Region of the window that is drawn; everything else is transparent.
+    shape: Option<HitRegion>,
 }
 
 impl Renderer {
@@ -24,11 +49,33 @@ impl Renderer {
             surface,
             width: size.width,
             height: size.height,
+            scale: 1,
+            frame: Vec::new(),
+            frame_valid: false,
+            shape: None,
+        }
+    }
+
+    /// Only draw the window inside `shape`, leaving the rest transparent.
+    pub fn set_shape(&mut self, shape: Option<HitRegion>) {
+        self.shape = shape;
+        self.frame_valid = false;
+    }
+
+    /// Draw each UI pixel as a `scale` x `scale` block of screen pixels.
+    pub fn set_scale(&mut self, scale: u32) {
+        let scale = scale.max(1);
+        if scale != self.scale {
+            self.scale = scale;
+            self.frame_valid = false;
         }
     }
 
     /// Resize the rendering surface.
     pub fn resize(&mut self, width: u32, height: u32) {
+        if width != self.width || height != self.height {
+            self.frame_valid = false;
+        }
         self.width = width;
         self.height = height;
 
@@ -39,17 +86,125 @@ impl Renderer {
 
     /// Render a View to the window.
     pub fn render(&mut self, view: &dyn View) {
-        let mut buffer = self.surface.buffer_mut().expect("Failed to get buffer");
-
+        let (ui_width, ui_height) = self.ui_size();
+        self.frame
+            .resize((ui_width * ui_height) as usize, CLEAR_COLOR);
         {
-            let mut canvas = Canvas::new(&mut buffer, self.width, self.height);
-            canvas.clear(0x000000); // Clear to black
+            let mut canvas = Canvas::new(&mut self.frame, ui_width, ui_height);
+            canvas.clear(CLEAR_COLOR);
             view.draw(&mut canvas);
         }
+        let screen = Rect::from_size(ui_width, ui_height);
+        self.cut_shape(&screen);
+        self.frame_valid = true;
 
+        let mut buffer = self.surface.buffer_mut().expect("Failed to get buffer");
+        copy_scaled(
+            &self.frame,
+            ui_width,
+            self.scale,
+            &mut buffer,
+            self.width,
+            &screen,
+        );
         buffer.present().expect("Failed to present buffer");
     }
 
+    /// Repaint and present only the damaged regions of a View.
+    /// Falls back to a full render when there is no previous frame to update.
+    pub fn render_damaged(&mut self, view: &dyn View, damage: &[Rect]) {
+        if !self.frame_valid {
+            self.render(view);
+            return;
+        }
+
+        let (ui_width, ui_height) = self.ui_size();
+        let screen = Rect::from_size(ui_width, ui_height);
+        let damage: Vec<Rect> = damage
+            .iter()
+            .map(|rect| rect.intersection(&screen))
+            .filter(|rect| !rect.is_empty())
+            .collect();
+        if damage.is_empty() {
+            return;
+        }
+
+        {
+            let mut canvas = Canvas::new(&mut self.frame, ui_width, ui_height);
+            for region in &damage {
+                let (x, y) = (region.x as u32, region.y as u32);
+                canvas.fill_rect(x, y, region.width, region.height, CLEAR_COLOR);
+                view.draw_region(&mut canvas, region);
+            }
+        }
+        for region in &damage {
+            self.cut_shape(region);
+        }
+
+        let surface = Rect::from_size(self.width, self.height);
+        let rects: Vec<softbuffer::Rect> = damage
+            .iter()
+            .map(|rect| self.to_screen(rect).intersection(&surface))
+            .filter_map(|rect| {
+                Some(softbuffer::Rect {
+                    x: rect.x as u32,
+                    y: rect.y as u32,
+                    width: NonZeroU32::new(rect.width)?,
+                    height: NonZeroU32::new(rect.height)?,
+                })
+            })
+            .collect();
+
+        let mut buffer = self.surface.buffer_mut().expect("Failed to get buffer");
+        // An age of 1 means the buffer still holds the previous frame, so
+        // only the damage needs updating
+        let copied = if buffer.age() == 1 {
+            &damage[..]
+        } else {
+            &[screen][..]
+        };
+        for region in copied {
+            copy_scaled(
+                &self.frame,
+                ui_width,
+                self.scale,
+                &mut buffer,
+                self.width,
+                region,
+            );
+        }
+        buffer
+            .present_with_damage(&rects)
+            .expect("Failed to present buffer");
+    }
+
+    /// Size of the frame in UI pixels, covering the whole surface.
+    fn ui_size(&self) -> (u32, u32) {
+        (
+            self.width.div_ceil(self.scale),
+            self.height.div_ceil(self.scale),
+        )
+    }
+
+    /// A rectangle of UI pixels in screen pixels.
+    fn to_screen(&self, rect: &Rect) -> Rect {
+        let scale = self.scale;
+        Rect::new(
+            rect.x * scale as i32,
+            rect.y * scale as i32,
+            rect.width * scale,
+            rect.height * scale,
+        )
+    }
+
+    /// Apply the window's shape to `region` of the frame.
+    fn cut_shape(&mut self, region: &Rect) {
+        if let Some(shape) = &self.shape {
+            let (ui_width, ui_height) = self.ui_size();
+            cut_shape(&mut self.frame, ui_width, ui_height, shape, region);
+        }
+    }
+
     pub fn width(&self) -> u32 {
         self.width
     }
@@ -57,4 +212,180 @@ impl Renderer {
     pub fn height(&self) -> u32 {
         self.height
     }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+}
+
+/// Copy `region` of a frame `frame_width` UI pixels wide into a surface
+/// buffer `buffer_width` pixels wide, repeating each pixel `scale` times in
+/// both directions. Rows and columns past the buffer's edges are dropped.
+fn copy_scaled(
+    frame: &[u32],
+    frame_width: u32,
+    scale: u32,
+    buffer: &mut [u32],
+    buffer_width: u32,
+    region: &Rect,
+) {
+    let (frame_width, scale, buffer_width) =
+        (frame_width as usize, scale as usize, buffer_width as usize);
+    let buffer_height = buffer.len() / buffer_width.max(1);
+    let left = region.x as usize * scale;
+    let right = (region.right() as usize * scale).min(buffer_width);
+    if left >= right {
+        return;
+    }
+
+    for y in region.y as usize..region.bottom() as usize {
+        let source = &frame[y * frame_width..(y + 1) * frame_width];
+        let first_row = y * scale;
+        let rows = first_row..((y + 1) * scale).min(buffer_height);
+        if rows.is_empty() {
+            break;
+        }
+        let start = first_row * buffer_width;
+        let target = &mut buffer[start + left..start + right];
+        if scale == 1 {
+            target.copy_from_slice(&source[left..right]);
+        } else {
+            for (i, pixel) in target.iter_mut().enumerate() {
+                *pixel = source[(left + i) / scale];
+            }
+        }
+        // Repeat the finished row for the rest of the block
+        for row in rows.skip(1) {
+            buffer.copy_within(start + left..start + right, row * buffer_width + left);
+        }
+    }
+}
+
+/// Make the pixels of `region` outside `shape` transparent and the ones
+/// inside it opaque, in a frame `width` x `height` pixels covered by the shape.
+///
+/// Surfaces without an alpha channel ignore the top byte, so there the
+/// outside is black rather than see-through.
+fn cut_shape(frame: &mut [u32], width: u32, height: u32, shape: &HitRegion, region: &Rect) {
+    let window = Rect::from_size(width, height);
+    for y in region.y..region.bottom() {
+        for x in region.x..region.right() {
+            let pixel = &mut frame[(y as u32 * width + x as u32) as usize];
+            *pixel = if shape.contains(&window, x, y) {
+                *pixel | OPAQUE
+            } else {
+                TRANSPARENT
+            };
+        }
+    }
+}
+
+/// Renders Views into an in-memory frame instead of a window surface.
+/// Used for screenshots and visual tests on machines without a display.
+pub struct OffscreenRenderer {
+    frame: Vec<u32>,
+    /// Frame size in UI pixels.
+    width: u32,
+    height: u32,
+    scale: u32,
+}
+
+impl OffscreenRenderer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            frame: vec![CLEAR_COLOR; (width * height) as usize],
+            width,
+            height,
+            scale: 1,
+        }
+    }
+
+    /// Present each UI pixel as a `scale` x `scale` block of image pixels,
+    /// the way a window at that scale does.
+    pub fn with_scale(mut self, scale: u32) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Render a View into the frame, replacing its previous contents.
+    pub fn render(&mut self, view: &dyn View) {
+        let mut canvas = Canvas::new(&mut self.frame, self.width, self.height);
+        canvas.clear(CLEAR_COLOR);
+        view.draw(&mut canvas);
+    }
+
+    /// The rendered pixels in UI pixels (0x00RRGGBB, row-major).
+    pub fn pixels(&self) -> &[u32] {
+        &self.frame
+    }
+
+    /// Convert the rendered frame, scaled up, to an opaque RGBA image.
+    pub fn to_image(&self) -> RgbaImage {
+        let (width, height) = (self.width * self.scale, self.height * self.scale);
+        let mut screen = vec![CLEAR_COLOR; (width * height) as usize];
+        let frame = Rect::from_size(self.width, self.height);
+        copy_scaled(
+            &self.frame,
+            self.width,
+            self.scale,
+            &mut screen,
+            width,
+            &frame,
+        );
+
+        RgbaImage::from_fn(width, height, |x, y| {
+            let pixel = screen[(y * width + x) as usize];
+            let [_, r, g, b] = pixel.to_be_bytes();
+            image::Rgba([r, g, b, 255])
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_scaled_repeats_pixels_in_blocks() {
+        // A 2x2 frame at 2x into a 3x3 buffer: the last row and column are cut off
+        let frame = [1, 2, 3, 4];
+        let mut buffer = [0u32; 9];
+        copy_scaled(&frame, 2, 2, &mut buffer, 3, &Rect::from_size(2, 2));
+        assert_eq!(buffer, [1, 1, 2, 1, 1, 2, 3, 3, 4]);
+
+        // Only the damaged pixel's block changes
+        let mut buffer = [0u32; 16];
+        copy_scaled(&frame, 2, 2, &mut buffer, 4, &Rect::new(1, 1, 1, 1));
+        assert_eq!(buffer[10..12], [4, 4]);
+        assert_eq!(buffer[14..16], [4, 4]);
+        assert_eq!(buffer.iter().filter(|&&p| p != 0).count(), 4);
+    }
+
+    #[test]
+    fn test_cut_shape_separates_inside_from_outside() {
+        // Black is drawn too, and must stay visible inside the shape
+        let mut frame = vec![0x336699; 16];
+        frame[5] = 0x000000;
+        cut_shape(
+            &mut frame,
+            4,
+            4,
+            &HitRegion::Ellipse,
+            &Rect::from_size(4, 4),
+        );
+
+        assert_eq!(frame[0], TRANSPARENT);
+        assert_eq!(frame[15], TRANSPARENT);
+        assert_eq!(frame[5], OPAQUE);
+        assert_eq!(frame[6], 0xFF336699);
+        assert!(frame.iter().all(|&p| p == TRANSPARENT || p >> 24 == 0xFF));
+    }
 }
@@ -0,0 +1,506 @@
+//! Anti-aliased vector drawing on `Canvas`: lines, rounded rectangles,
+//! circles, ellipses and polygons, filled with solid colors or gradients.
+//!
+//! Coordinates are in pixels with pixel centers at `x + 0.5`, so a circle at
+//! `(10.0, 10.0)` is centered on the corner between four pixels.
+
+use crate::core::Rect;
+use crate::graphics::Canvas;
+
+/// Vertical samples per pixel row when filling polygons.
+const POLYGON_SUBSAMPLES: u32 = 4;
+
+/// A color position along a gradient.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GradientStop {
+    /// Position along the gradient, from 0.0 to 1.0.
+    pub offset: f32,
+    pub color: u32,
+    pub alpha: u8,
+}
+
+impl GradientStop {
+    /// An opaque stop.
+    pub fn new(offset: f32, color: u32) -> Self {
+        Self {
+            offset,
+            color,
+            alpha: 255,
+        }
+    }
+
+    /// Set the stop's opacity.
+    pub fn with_alpha(mut self, alpha: u8) -> Self {
+        self.alpha = alpha;
+        self
+    }
+}
+
+/// How shapes are filled.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Paint {
+    /// A single color, composited with `alpha`.
+    Solid { color: u32, alpha: u8 },
+    /// Colors varying along the line from `start` to `end`.
+    Linear {
+        start: (f32, f32),
+        end: (f32, f32),
+        stops: Vec<GradientStop>,
+    },
+    /// Colors varying with the distance from `center`.
+    Radial {
+        center: (f32, f32),
+        radius: f32,
+        stops: Vec<GradientStop>,
+    },
+}
+
+impl Paint {
+    /// An opaque color.
+    pub fn solid(color: u32) -> Self {
+        Paint::Solid { color, alpha: 255 }
+    }
+
+    /// A color composited with the given opacity.
+    pub fn translucent(color: u32, alpha: u8) -> Self {
+        Paint::Solid { color, alpha }
+    }
+
+    /// A linear gradient. Stops are sorted by offset.
+    pub fn linear(start: (f32, f32), end: (f32, f32), mut stops: Vec<GradientStop>) -> Self {
+        stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+        Paint::Linear { start, end, stops }
+    }
+
+    /// A radial gradient. Stops are sorted by offset.
+    pub fn radial(center: (f32, f32), radius: f32, mut stops: Vec<GradientStop>) -> Self {
+        stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+        Paint::Radial {
+            center,
+            radius,
+            stops,
+        }
+    }
+
+    /// The color and opacity at a point.
+    pub fn sample(&self, x: f32, y: f32) -> (u32, u8) {
+        match self {
+            Paint::Solid { color, alpha } => (*color, *alpha),
+            Paint::Linear { start, end, stops } => {
+                let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+                let len_sq = dx * dx + dy * dy;
+                let t = if len_sq > 0.0 {
+                    ((x - start.0) * dx + (y - start.1) * dy) / len_sq
+                } else {
+                    0.0
+                };
+                gradient_at(stops, t)
+            }
+            Paint::Radial {
+                center,
+                radius,
+                stops,
+            } => {
+                let distance = (x - center.0).hypot(y - center.1);
+                let t = if *radius > 0.0 {
+                    distance / radius
+                } else {
+                    1.0
+                };
+                gradient_at(stops, t)
+            }
+        }
+    }
+}
+
+/// Interpolate the gradient color at `t`, clamped to the first and last stop.
+fn gradient_at(stops: &[GradientStop], t: f32) -> (u32, u8) {
+    let (Some(first), Some(last)) = (stops.first(), stops.last()) else {
+        return (0, 0);
+    };
+    if t <= first.offset {
+        return (first.color, first.alpha);
+    }
+    if t >= last.offset {
+        return (last.color, last.alpha);
+    }
+
+    let i = stops
+        .iter()
+        .position(|s| s.offset > t)
+        .unwrap_or(stops.len() - 1);
+    let (a, b) = (&stops[i - 1], &stops[i]);
+    let span = b.offset - a.offset;
+    let f = if span > 0.0 {
+        (t - a.offset) / span
+    } else {
+        1.0
+    };
+
+    let lerp = |from: u32, to: u32| (from as f32 + (to as f32 - from as f32) * f).round() as u32;
+    let channel = |shift: u32| lerp((a.color >> shift) & 0xFF, (b.color >> shift) & 0xFF) << shift;
+    let color = channel(16) | channel(8) | channel(0);
+    (color, lerp(a.alpha as u32, b.alpha as u32) as u8)
+}
+
+/// Pixel coverage of a shape edge given the signed distance from the pixel
+/// center to the edge (negative inside).
+#[inline]
+fn coverage(distance: f32) -> f32 {
+    (0.5 - distance).clamp(0.0, 1.0)
+}
+
+/// Distance from a point to the segment `a`-`b`.
+fn segment_distance(px: f32, py: f32, a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq > 0.0 {
+        (((px - a.0) * dx + (py - a.1) * dy) / len_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (px - (a.0 + t * dx)).hypot(py - (a.1 + t * dy))
+}
+
+/// Signed distance from a point to a rounded rectangle.
+fn rounded_rect_distance(px: f32, py: f32, rect: &Rect, radius: f32) -> f32 {
+    let half_w = rect.width as f32 / 2.0;
+    let half_h = rect.height as f32 / 2.0;
+    let radius = radius.clamp(0.0, half_w.min(half_h));
+    let qx = (px - (rect.x as f32 + half_w)).abs() - (half_w - radius);
+    let qy = (py - (rect.y as f32 + half_h)).abs() - (half_h - radius);
+    let outside = qx.max(0.0).hypot(qy.max(0.0));
+    outside + qx.max(qy).min(0.0) - radius
+}
+
+/// Approximate signed distance from a point to an ellipse.
+fn ellipse_distance(px: f32, py: f32, cx: f32, cy: f32, rx: f32, ry: f32) -> f32 {
+    if rx <= 0.0 || ry <= 0.0 {
+        return f32::INFINITY;
+    }
+    let (x, y) = (px - cx, py - cy);
+    let f = (x * x) / (rx * rx) + (y * y) / (ry * ry) - 1.0;
+    let gradient = 2.0 * ((x * x) / rx.powi(4) + (y * y) / ry.powi(4)).sqrt();
+    if gradient > 0.0 {
+        f / gradient
+    } else {
+        -rx.min(ry)
+    }
+}
+
+impl Canvas<'_> {
+    /// Fill a rectangle with a paint, compositing translucent colors.
+    pub fn fill_rect_with(&mut self, rect: Rect, paint: &Paint) {
+        let x0 = rect.x.max(0);
+        let y0 = rect.y.max(0);
+        let x1 = rect.right().min(self.width() as i32);
+        let y1 = rect.bottom().min(self.height() as i32);
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let (color, alpha) = paint.sample(x as f32 + 0.5, y as f32 + 0.5);
+                self.blend_pixel(x as u32, y as u32, color, alpha);
+            }
+        }
+    }
+
+    /// Draw an anti-aliased line with round caps.
+    pub fn draw_line(&mut self, from: (f32, f32), to: (f32, f32), width: f32, paint: &Paint) {
+        let half = width / 2.0;
+        let bounds = (
+            from.0.min(to.0) - half,
+            from.1.min(to.1) - half,
+            from.0.max(to.0) + half,
+            from.1.max(to.1) + half,
+        );
+        self.fill_distance(bounds, paint, |x, y| {
+            segment_distance(x, y, from, to) - half
+        });
+    }
+
+    /// Draw connected line segments, closing the shape if `closed`.
+    /// Joins are drawn once, so translucent strokes don't darken at corners.
+    pub fn draw_polyline(
+        &mut self,
+        points: &[(f32, f32)],
+        closed: bool,
+        width: f32,
+        paint: &Paint,
+    ) {
+        if points.is_empty() {
+            return;
+        }
+        let half = width / 2.0;
+        let mut segments: Vec<_> = points.windows(2).map(|w| (w[0], w[1])).collect();
+        if closed && points.len() > 2 {
+            segments.push((points[points.len() - 1], points[0]));
+        }
+        if segments.is_empty() {
+            segments.push((points[0], points[0]));
+        }
+
+        let bounds = expand(points_bounds(points), half);
+        self.fill_distance(bounds, paint, |x, y| {
+            let distance = segments
+                .iter()
+                .map(|&(a, b)| segment_distance(x, y, a, b))
+                .fold(f32::INFINITY, f32::min);
+            distance - half
+        });
+    }
+
+    /// Fill a rectangle with rounded corners.
+    pub fn fill_rounded_rect(&mut self, rect: Rect, radius: f32, paint: &Paint) {
+        let bounds = rect_bounds(&rect);
+        self.fill_distance(bounds, paint, |x, y| {
+            rounded_rect_distance(x, y, &rect, radius)
+        });
+    }
+
+    /// Outline a rectangle with rounded corners. The stroke is centered on
+    /// the rectangle's edge.
+    pub fn stroke_rounded_rect(&mut self, rect: Rect, radius: f32, width: f32, paint: &Paint) {
+        let half = width / 2.0;
+        let bounds = expand(rect_bounds(&rect), half);
+        self.fill_distance(bounds, paint, |x, y| {
+            rounded_rect_distance(x, y, &rect, radius).abs() - half
+        });
+    }
+
+    /// Fill a circle.
+    pub fn fill_circle(&mut self, center: (f32, f32), radius: f32, paint: &Paint) {
+        let bounds = expand((center.0, center.1, center.0, center.1), radius);
+        self.fill_distance(bounds, paint, |x, y| {
+            (x - center.0).hypot(y - center.1) - radius
+        });
+    }
+
+    /// Outline a circle. The stroke is centered on the radius.
+    pub fn stroke_circle(&mut self, center: (f32, f32), radius: f32, width: f32, paint: &Paint) {
+        let half = width / 2.0;
+        let bounds = expand((center.0, center.1, center.0, center.1), radius + half);
+        self.fill_distance(bounds, paint, |x, y| {
+            ((x - center.0).hypot(y - center.1) - radius).abs() - half
+        });
+    }
+
+    /// Fill an ellipse with radii `rx` and `ry`.
+    pub fn fill_ellipse(&mut self, center: (f32, f32), rx: f32, ry: f32, paint: &Paint) {
+        let (cx, cy) = center;
+        let bounds = (cx - rx, cy - ry, cx + rx, cy + ry);
+        self.fill_distance(bounds, paint, |x, y| ellipse_distance(x, y, cx, cy, rx, ry));
+    }
+
+    /// Outline an ellipse. The stroke is centered on the ellipse's edge.
+    pub fn stroke_ellipse(
+        &mut self,
+        center: (f32, f32),
+        rx: f32,
+        ry: f32,
+        width: f32,
+        paint: &Paint,
+    ) {
+        let (cx, cy) = center;
+        let half = width / 2.0;
+        let bounds = (
+            cx - rx - half,
+            cy - ry - half,
+            cx + rx + half,
+            cy + ry + half,
+        );
+        self.fill_distance(bounds, paint, |x, y| {
+            ellipse_distance(x, y, cx, cy, rx, ry).abs() - half
+        });
+    }
+
+    /// Fill a polygon using the non-zero winding rule. The last point
+    /// connects back to the first.
+    pub fn fill_polygon(&mut self, points: &[(f32, f32)], paint: &Paint) {
+        if points.len() < 3 {
+            return;
+        }
+        let (min_x, min_y, max_x, max_y) = points_bounds(points);
+        let Some((x0, y0, x1, y1)) = self.pixel_bounds((min_x, min_y, max_x, max_y)) else {
+            return;
+        };
+
+        let edges: Vec<_> = (0..points.len())
+            .map(|i| (points[i], points[(i + 1) % points.len()]))
+            .filter(|(a, b)| a.1 != b.1)
+            .collect();
+        let row_width = (x1 - x0) as usize;
+        let mut row = vec![0.0f32; row_width];
+        let mut crossings: Vec<(f32, i32)> = Vec::new();
+        let weight = 1.0 / POLYGON_SUBSAMPLES as f32;
+
+        for y in y0..y1 {
+            row.fill(0.0);
+            for s in 0..POLYGON_SUBSAMPLES {
+                let sy = y as f32 + (s as f32 + 0.5) * weight;
+                crossings.clear();
+                for &(a, b) in &edges {
+                    let (top, bottom, dir) = if a.1 < b.1 { (a, b, 1) } else { (b, a, -1) };
+                    if sy >= top.1 && sy < bottom.1 {
+                        let t = (sy - top.1) / (bottom.1 - top.1);
+                        crossings.push((top.0 + t * (bottom.0 - top.0), dir));
+                    }
+                }
+                crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+                let mut winding = 0;
+                for pair in crossings.windows(2) {
+                    winding += pair[0].1;
+                    if winding != 0 {
+                        let start = pair[0].0 - x0 as f32;
+                        let end = pair[1].0 - x0 as f32;
+                        add_span(&mut row, start, end, weight);
+                    }
+                }
+            }
+
+            for (i, &cov) in row.iter().enumerate() {
+                if cov <= 0.0 {
+                    continue;
+                }
+                let x = x0 + i as i32;
+                let (color, alpha) = paint.sample(x as f32 + 0.5, y as f32 + 0.5);
+                let alpha = (alpha as f32 * cov.min(1.0)).round() as u8;
+                self.blend_pixel(x as u32, y as u32, color, alpha);
+            }
+        }
+    }
+
+    /// Outline a closed polygon.
+    pub fn stroke_polygon(&mut self, points: &[(f32, f32)], width: f32, paint: &Paint) {
+        self.draw_polyline(points, true, width, paint);
+    }
+
+    /// Composite `paint` over every pixel of `bounds` (min x, min y, max x,
+    /// max y), weighted by the coverage from `distance`.
+    fn fill_distance(
+        &mut self,
+        bounds: (f32, f32, f32, f32),
+        paint: &Paint,
+        distance: impl Fn(f32, f32) -> f32,
+    ) {
+        let Some((x0, y0, x1, y1)) = self.pixel_bounds(bounds) else {
+            return;
+        };
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let (cx, cy) = (x as f32 + 0.5, y as f32 + 0.5);
+                let cov = coverage(distance(cx, cy));
+                if cov <= 0.0 {
+                    continue;
+                }
+                let (color, alpha) = paint.sample(cx, cy);
+                let alpha = (alpha as f32 * cov).round() as u8;
+                self.blend_pixel(x as u32, y as u32, color, alpha);
+            }
+        }
+    }
+
+    /// The pixel range covering `bounds`, limited to the canvas and clip rect.
+    fn pixel_bounds(&self, bounds: (f32, f32, f32, f32)) -> Option<(i32, i32, i32, i32)> {
+        let mut area = Rect::from_size(self.width(), self.height());
+        if let Some(clip) = self.clip_rect() {
+            area = area.intersection(clip);
+        }
+        let x0 = (bounds.0.floor() as i32 - 1).max(area.x);
+        let y0 = (bounds.1.floor() as i32 - 1).max(area.y);
+        let x1 = (bounds.2.ceil() as i32 + 1).min(area.right());
+        let y1 = (bounds.3.ceil() as i32 + 1).min(area.bottom());
+        (x0 < x1 && y0 < y1).then_some((x0, y0, x1, y1))
+    }
+}
+
+fn rect_bounds(rect: &Rect) -> (f32, f32, f32, f32) {
+    (
+        rect.x as f32,
+        rect.y as f32,
+        rect.right() as f32,
+        rect.bottom() as f32,
+    )
+}
+
+fn points_bounds(points: &[(f32, f32)]) -> (f32, f32, f32, f32) {
+    points.iter().fold(
+        (
+            f32::INFINITY,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            f32::NEG_INFINITY,
+        ),
+        |(x0, y0, x1, y1), &(x, y)| (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+    )
+}
+
+fn expand(bounds: (f32, f32, f32, f32), by: f32) -> (f32, f32, f32, f32) {
+    (bounds.0 - by, bounds.1 - by, bounds.2 + by, bounds.3 + by)
+}
+
+/// Add `weight` of coverage for the horizontal span `[start, end)` to a row,
+/// with partial coverage for the pixels the span starts and ends in.
+fn add_span(row: &mut [f32], start: f32, end: f32, weight: f32) {
+    let start = start.max(0.0);
+    let end = end.min(row.len() as f32);
+    if end <= start {
+        return;
+    }
+
+    let first = start.floor() as usize;
+    let last = end.ceil() as usize - 1;
+    if first == last {
+        row[first] += (end - start) * weight;
+        return;
+    }
+    row[first] += (first as f32 + 1.0 - start) * weight;
+    for cov in &mut row[first + 1..last] {
+        *cov += weight;
+    }
+    row[last] += (end - last as f32) * weight;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gradient_interpolates_between_stops() {
+        let paint = Paint::linear(
+            (0.0, 0.0),
+            (10.0, 0.0),
+            vec![
+                GradientStop::new(1.0, 0xFFFFFF),
+                GradientStop::new(0.0, 0x000000).with_alpha(0),
+            ],
+        );
+
+        assert_eq!(paint.sample(-5.0, 0.0), (0x000000, 0));
+        assert_eq!(paint.sample(5.0, 3.0), (0x808080, 128));
+        assert_eq!(paint.sample(20.0, 0.0), (0xFFFFFF, 255));
+    }
+
+    #[test]
+    fn test_shapes_are_antialiased() {
+        let mut buffer = vec![0x000000; 20 * 20];
+        let mut canvas = Canvas::new(&mut buffer, 20, 20);
+        canvas.fill_circle((10.0, 10.0), 6.0, &Paint::solid(0xFFFFFF));
+
+        // Center fully covered, corners untouched, edge pixels partially blended
+        assert_eq!(buffer[10 * 20 + 10], 0xFFFFFF);
+        assert_eq!(buffer[0], 0x000000);
+        assert!(buffer.iter().any(|&p| p != 0 && p != 0xFFFFFF));
+
+        let mut buffer = vec![0x000000; 10 * 10];
+        let mut canvas = Canvas::new(&mut buffer, 10, 10);
+        let square = [(2.0, 2.0), (6.5, 2.0), (6.5, 6.0), (2.0, 6.0)];
+        canvas.fill_polygon(&square, &Paint::solid(0xFFFFFF));
+
+        assert_eq!(buffer[3 * 10 + 3], 0xFFFFFF);
+        assert_eq!(buffer[3 * 10 + 6], 0x808080);
+        assert_eq!(buffer[3 * 10 + 7], 0x000000);
+        assert_eq!(buffer[6 * 10 + 3], 0x000000);
+    }
+}
@@ -0,0 +1,471 @@
+//! Drawable skin assets.
+//!
+//! A `Sprite` wraps one or more loaded RGBA frames and knows how to draw itself
+//! into a destination rectangle. Plain sprites are drawn at their native size;
+//! nine-slice sprites keep their corners intact and stretch or tile the
+//! edges and center to fill the destination. Animated sprites pick the frame
+//! to draw from the time elapsed since the animation started.
+
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+use image::RgbaImage;
+
+use crate::core::Rect;
+use crate::graphics::Canvas;
+
+/// How the scalable regions of a nine-slice are filled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SliceMode {
+    /// Scale the source region to fill the destination (nearest neighbour).
+    #[default]
+    Stretch,
+    /// Repeat the source region at its native size.
+    Tile,
+}
+
+/// Nine-slice insets and fill modes.
+///
+/// The insets (in source pixels) split the image into a 3x3 grid. Corners are
+/// copied as-is, top/bottom edges scale horizontally, left/right edges scale
+/// vertically and the center scales in both directions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NineSlice {
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    /// Fill mode for the four edges.
+    pub edges: SliceMode,
+    /// Fill mode for the center region.
+    pub center: SliceMode,
+}
+
+/// How an animated sprite plays its frames.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Playback {
+    /// Repeat the animation for as long as the sprite is shown.
+    #[default]
+    Loop,
+    /// Play once each time the sprite starts being shown, then hold the last frame.
+    Once,
+}
+
+/// An image asset that can be drawn at its native size or scaled into a rect.
+#[derive(Debug, Clone)]
+pub struct Sprite {
+    /// Animation frames; static sprites have exactly one.
+    frames: Vec<RgbaImage>,
+    /// Display time of each frame.
+    delays: Vec<Duration>,
+    playback: Playback,
+    slice: Option<NineSlice>,
+}
+
+impl Sprite {
+    /// Create a plain sprite that is always drawn at its native size.
+    pub fn new(image: RgbaImage) -> Self {
+        Self {
+            frames: vec![image],
+            delays: vec![Duration::ZERO],
+            playback: Playback::Loop,
+            slice: None,
+        }
+    }
+
+    /// Create a nine-slice sprite that scales to fill its destination.
+    pub fn nine_slice(image: RgbaImage, slice: NineSlice) -> Self {
+        Self::new(image).with_nine_slice(slice)
+    }
+
+    /// Create an animated sprite from frames and their display times.
+    /// Panics if `frames` is empty.
+    pub fn animated(frames: Vec<(RgbaImage, Duration)>, playback: Playback) -> Self {
+        assert!(
+            !frames.is_empty(),
+            "animated sprite needs at least one frame"
+        );
+        let (frames, delays) = frames.into_iter().unzip();
+        Self {
+            frames,
+            delays,
+            playback,
+            slice: None,
+        }
+    }
+
+    /// Scale every frame with the given nine-slice settings.
+    pub fn with_nine_slice(mut self, slice: NineSlice) -> Self {
+        self.slice = Some(slice);
+        self
+    }
+
+    /// Get the underlying image (the first frame for animations).
+    pub fn image(&self) -> &RgbaImage {
+        &self.frames[0]
+    }
+
+    /// Check if this sprite has more than one frame.
+    pub fn is_animated(&self) -> bool {
+        self.frames.len() > 1
+    }
+
+    /// Number of animation frames.
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Get the playback mode.
+    pub fn playback(&self) -> Playback {
+        self.playback
+    }
+
+    /// Get the frame shown `elapsed` after the animation started.
+    pub fn frame_at(&self, elapsed: Duration) -> &RgbaImage {
+        &self.frames[self.frame_timing(elapsed).0]
+    }
+
+    /// Time until the frame shown at `elapsed` is replaced by the next one.
+    /// Returns `None` for static sprites and finished one-shot animations.
+    pub fn next_change(&self, elapsed: Duration) -> Option<Duration> {
+        if !self.is_animated() {
+            return None;
+        }
+        self.frame_timing(elapsed).1
+    }
+
+    /// Index of the frame at `elapsed` and the time remaining until it ends.
+    fn frame_timing(&self, elapsed: Duration) -> (usize, Option<Duration>) {
+        let total: Duration = self.delays.iter().sum();
+        if total.is_zero() {
+            return (0, None);
+        }
+
+        let t = match self.playback {
+            Playback::Loop => Duration::from_nanos((elapsed.as_nanos() % total.as_nanos()) as u64),
+            Playback::Once if elapsed >= total => return (self.frames.len() - 1, None),
+            Playback::Once => elapsed,
+        };
+
+        let mut end = Duration::ZERO;
+        for (i, delay) in self.delays.iter().enumerate() {
+            end += *delay;
+            if t < end {
+                return (i, Some(end - t));
+            }
+        }
+        (self.frames.len() - 1, None)
+    }
+
+    /// Get the nine-slice definition, if any.
+    pub fn slice(&self) -> Option<&NineSlice> {
+        self.slice.as_ref()
+    }
+
+    /// Check if this sprite scales to fill its destination.
+    pub fn is_scalable(&self) -> bool {
+        self.slice.is_some()
+    }
+
+    /// Native width of the image.
+    pub fn width(&self) -> u32 {
+        self.image().width()
+    }
+
+    /// Native height of the image.
+    pub fn height(&self) -> u32 {
+        self.image().height()
+    }
+
+    /// Draw the sprite at its native size with its top-left corner at (x, y).
+    pub fn draw(&self, canvas: &mut Canvas, x: i32, y: i32, clip: Option<&Rect>) {
+        self.draw_at(canvas, x, y, clip, Duration::ZERO);
+    }
+
+    /// Like `draw`, showing the animation frame at `elapsed`.
+    pub fn draw_at(
+        &self,
+        canvas: &mut Canvas,
+        x: i32,
+        y: i32,
+        clip: Option<&Rect>,
+        elapsed: Duration,
+    ) {
+        canvas.draw_image(self.frame_at(elapsed), x, y, clip);
+    }
+
+    /// Draw the sprite into `dest`.
+    /// Nine-slice sprites fill the whole rect; plain sprites are drawn at their
+    /// native size anchored at the top-left and clipped to `dest`.
+    pub fn draw_in(&self, canvas: &mut Canvas, dest: &Rect, clip: Option<&Rect>) {
+        self.draw_in_at(canvas, dest, clip, Duration::ZERO);
+    }
+
+    /// Like `draw_in`, showing the animation frame at `elapsed`.
+    pub fn draw_in_at(
+        &self,
+        canvas: &mut Canvas,
+        dest: &Rect,
+        clip: Option<&Rect>,
+        elapsed: Duration,
+    ) {
+        let clip = match clip {
+            Some(clip) => dest.intersection(clip),
+            None => *dest,
+        };
+        let image = self.frame_at(elapsed);
+
+        match &self.slice {
+            Some(slice) => draw_nine_slice(canvas, image, slice, dest, &clip),
+            None => canvas.draw_image(image, dest.x, dest.y, Some(&clip)),
+        }
+    }
+}
+
+/// Tracks when a widget started showing its current sprite, so animations
+/// restart whenever the widget switches state (e.g. normal to hover).
+///
+/// Widgets draw through `&self`, so the start time lives in a `Cell`.
+#[derive(Debug, Default)]
+pub struct Playhead {
+    current: Cell<Option<(usize, Instant)>>,
+}
+
+impl Playhead {
+    /// Create a playhead that has not shown anything yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time since the sprite identified by `key` started showing.
+    /// Switching to a different key restarts the clock.
+    pub fn elapsed(&self, key: usize) -> Duration {
+        let now = Instant::now();
+        match self.current.get() {
+            Some((current, start)) if current == key => now - start,
+            _ => {
+                self.current.set(Some((key, now)));
+                Duration::ZERO
+            }
+        }
+    }
+
+    /// Key of the sprite currently being shown, if anything has been drawn.
+    pub fn current(&self) -> Option<usize> {
+        self.current.get().map(|(key, _)| key)
+    }
+
+    /// When `sprite` (the one currently shown) next changes frame.
+    pub fn next_frame(&self, sprite: &Sprite) -> Option<Instant> {
+        let (_, start) = self.current.get()?;
+        let now = Instant::now();
+        sprite
+            .next_change(now - start)
+            .map(|remaining| now + remaining)
+    }
+}
+
+/// Draw `image` into `dest` using nine-slice scaling.
+fn draw_nine_slice(
+    canvas: &mut Canvas,
+    image: &RgbaImage,
+    slice: &NineSlice,
+    dest: &Rect,
+    clip: &Rect,
+) {
+    let (src_w, src_h) = image.dimensions();
+
+    // Clamp insets to the source image, then shrink them proportionally if
+    // the destination is too small to hold both corners.
+    let left = slice.left.min(src_w);
+    let right = slice.right.min(src_w - left);
+    let top = slice.top.min(src_h);
+    let bottom = slice.bottom.min(src_h - top);
+
+    let (dest_left, dest_right) = fit_insets(left, right, dest.width);
+    let (dest_top, dest_bottom) = fit_insets(top, bottom, dest.height);
+
+    let src_cols = [
+        (0, left),
+        (left, src_w - left - right),
+        (src_w - right, right),
+    ];
+    let src_rows = [
+        (0, top),
+        (top, src_h - top - bottom),
+        (src_h - bottom, bottom),
+    ];
+    let dest_cols = [
+        (0, dest_left),
+        (dest_left, dest.width - dest_left - dest_right),
+        (dest.width - dest_right, dest_right),
+    ];
+    let dest_rows = [
+        (0, dest_top),
+        (dest_top, dest.height - dest_top - dest_bottom),
+        (dest.height - dest_bottom, dest_bottom),
+    ];
+
+    for row in 0..3 {
+        for col in 0..3 {
+            let mode = if row == 1 && col == 1 {
+                slice.center
+            } else {
+                slice.edges
+            };
+            // Corners never tile; edges only tile along their long axis.
+            let tile_x = col == 1 && mode == SliceMode::Tile;
+            let tile_y = row == 1 && mode == SliceMode::Tile;
+
+            let (sx, sw) = src_cols[col];
+            let (sy, sh) = src_rows[row];
+            let (dx, dw) = dest_cols[col];
+            let (dy, dh) = dest_rows[row];
+
+            let cell = Rect::new(dest.x + dx as i32, dest.y + dy as i32, dw, dh);
+            blit_region(
+                canvas,
+                image,
+                (sx, sy, sw, sh),
+                &cell,
+                (tile_x, tile_y),
+                clip,
+            );
+        }
+    }
+}
+
+/// Map a source region onto a destination cell, stretching or tiling per axis.
+fn blit_region(
+    canvas: &mut Canvas,
+    image: &RgbaImage,
+    (sx, sy, sw, sh): (u32, u32, u32, u32),
+    cell: &Rect,
+    (tile_x, tile_y): (bool, bool),
+    clip: &Rect,
+) {
+    if sw == 0 || sh == 0 || cell.width == 0 || cell.height == 0 {
+        return;
+    }
+
+    for dy in 0..cell.height {
+        let py = cell.y + dy as i32;
+        if py < clip.y || py >= clip.bottom() || py < 0 {
+            continue;
+        }
+        let src_y = sy
+            + if tile_y {
+                dy % sh
+            } else {
+                dy * sh / cell.height
+            };
+
+        for dx in 0..cell.width {
+            let px = cell.x + dx as i32;
+            if px < clip.x || px >= clip.right() || px < 0 {
+                continue;
+            }
+            let src_x = sx
+                + if tile_x {
+                    dx % sw
+                } else {
+                    dx * sw / cell.width
+                };
+
+            let [r, g, b, a] = image.get_pixel(src_x, src_y).0;
+            canvas.blend_pixel_rgba(px as u32, py as u32, r, g, b, a);
+        }
+    }
+}
+
+/// Shrink a pair of insets proportionally so they fit within `size`.
+fn fit_insets(start: u32, end: u32, size: u32) -> (u32, u32) {
+    let total = start + end;
+    if total <= size {
+        (start, end)
+    } else {
+        let fitted_start = start * size / total;
+        (fitted_start, size - fitted_start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    /// A 3x3 image with a distinct color per cell, sliced with 1px insets.
+    fn grid_sprite(edges: SliceMode, center: SliceMode) -> Sprite {
+        let mut image = RgbaImage::new(3, 3);
+        for (x, y, pixel) in image.enumerate_pixels_mut() {
+            *pixel = Rgba([(y * 3 + x) as u8, 0, 0, 255]);
+        }
+        let slice = NineSlice {
+            left: 1,
+            top: 1,
+            right: 1,
+            bottom: 1,
+            edges,
+            center,
+        };
+        Sprite::nine_slice(image, slice)
+    }
+
+    #[test]
+    fn test_nine_slice_keeps_corners_and_fills_rect() {
+        let sprite = grid_sprite(SliceMode::Stretch, SliceMode::Stretch);
+        let mut buffer = vec![0xFFFFFF; 6 * 4];
+        let mut canvas = Canvas::new(&mut buffer, 6, 4);
+        sprite.draw_in(&mut canvas, &Rect::new(0, 0, 6, 4), None);
+
+        let cell = |x: usize, y: usize| buffer[y * 6 + x] >> 16;
+        assert_eq!(cell(0, 0), 0);
+        assert_eq!(cell(5, 0), 2);
+        assert_eq!(cell(0, 3), 6);
+        assert_eq!(cell(5, 3), 8);
+        assert_eq!(cell(3, 0), 1);
+        assert_eq!(cell(0, 2), 3);
+        assert_eq!(cell(3, 2), 4);
+    }
+
+    #[test]
+    fn test_nine_slice_shrinks_insets_for_small_rects() {
+        assert_eq!(fit_insets(4, 4, 10), (4, 4));
+        assert_eq!(fit_insets(4, 4, 6), (3, 3));
+        assert_eq!(fit_insets(6, 2, 4), (3, 1));
+    }
+
+    #[test]
+    fn test_animation_frame_timing() {
+        let frame = |r| {
+            (
+                RgbaImage::from_pixel(1, 1, Rgba([r, 0, 0, 255])),
+                Duration::from_millis(100),
+            )
+        };
+        let looping = Sprite::animated(vec![frame(1), frame(2)], Playback::Loop);
+        let ms = Duration::from_millis;
+
+        assert_eq!(looping.frame_at(ms(50)).get_pixel(0, 0).0[0], 1);
+        assert_eq!(looping.frame_at(ms(150)).get_pixel(0, 0).0[0], 2);
+        assert_eq!(looping.frame_at(ms(250)).get_pixel(0, 0).0[0], 1);
+        assert_eq!(looping.next_change(ms(130)), Some(ms(70)));
+
+        let once = Sprite::animated(vec![frame(1), frame(2)], Playback::Once);
+        assert_eq!(once.frame_at(ms(250)).get_pixel(0, 0).0[0], 2);
+        assert_eq!(once.next_change(ms(250)), None);
+        assert_eq!(Sprite::new(RgbaImage::new(1, 1)).next_change(ms(0)), None);
+    }
+
+    #[test]
+    fn test_plain_sprite_draws_at_native_size() {
+        let sprite = Sprite::new(RgbaImage::from_pixel(2, 2, Rgba([255, 0, 0, 255])));
+        let mut buffer = vec![0; 4 * 4];
+        let mut canvas = Canvas::new(&mut buffer, 4, 4);
+        sprite.draw_in(&mut canvas, &Rect::new(0, 0, 4, 4), None);
+
+        assert_eq!(buffer[0], 0xFF0000);
+        assert_eq!(buffer[5], 0xFF0000);
+        assert_eq!(buffer[10], 0);
+    }
+}
@@ -0,0 +1,342 @@
+//! Bitmap fonts drawn from pre-rendered glyph images: BMFont files and
+//! fixed-grid character sheets.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use fontdue::{LineMetrics, Metrics};
+use image::imageops::{self, FilterType};
+use image::RgbaImage;
+
+use super::glyph_cache::Glyph;
+use super::FontError;
+
+/// One character's image and placement, in the font's native pixels.
+#[derive(Debug)]
+struct BitmapChar {
+    image: RgbaImage,
+    /// Offset of the image from the pen position.
+    x_offset: i32,
+    /// Offset of the image from the top of the line.
+    y_offset: i32,
+    advance: i32,
+}
+
+/// A font whose glyphs are images with their colors baked in.
+///
+/// Glyphs are only scaled by whole multiples of the size they were drawn
+/// at, so pixel text stays crisp at any `font_size`.
+#[derive(Debug)]
+pub struct BitmapFont {
+    name: Option<String>,
+    /// Size the glyphs were drawn at.
+    size: u32,
+    line_height: u32,
+    /// Distance from the top of a line to the baseline.
+    base: u32,
+    chars: HashMap<char, BitmapChar>,
+    /// Extra advance after the first character of a pair.
+    kernings: HashMap<(char, char), i32>,
+}
+
+impl BitmapFont {
+    /// Load a BMFont `.fnt` file (text format) and its page images.
+    pub fn load_bmfont(path: &Path) -> Result<Self, FontError> {
+        let text = std::fs::read_to_string(path).map_err(FontError::Io)?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+        Self::parse_bmfont(&text, |file| {
+            let image = image::open(dir.join(file)).map_err(FontError::Image)?;
+            Ok(image.to_rgba8())
+        })
+    }
+
+    /// Parse BMFont text, getting each page image by its file name from `load_page`.
+    pub fn parse_bmfont(
+        text: &str,
+        mut load_page: impl FnMut(&str) -> Result<RgbaImage, FontError>,
+    ) -> Result<Self, FontError> {
+        let mut name = None;
+        let mut size = None;
+        let mut common = None;
+        let mut pages = HashMap::new();
+        let mut chars = HashMap::new();
+        let mut kernings = HashMap::new();
+
+        for line in text.lines() {
+            let (tag, attrs) = parse_line(line);
+            match tag {
+                "info" => {
+                    name = attrs.get("face").map(|face| face.to_string());
+                    // A negative size means "match character height"
+                    size = Some(number(&attrs, tag, "size")?.unsigned_abs());
+                }
+                "common" => {
+                    let line_height = number(&attrs, tag, "lineHeight")?.max(1) as u32;
+                    let base = number(&attrs, tag, "base")?.max(0) as u32;
+                    common = Some((line_height, base));
+                }
+                "page" => {
+                    let file = attrs
+                        .get("file")
+                        .ok_or_else(|| FontError::Parse("page without a file".to_string()))?;
+                    pages.insert(number(&attrs, tag, "id")?, load_page(file)?);
+                }
+                "char" => {
+                    let id = number(&attrs, tag, "id")?;
+                    let Some(c) = char::from_u32(id as u32) else {
+                        continue;
+                    };
+                    let page = number(&attrs, tag, "page")?;
+                    let page = pages.get(&page).ok_or_else(|| {
+                        FontError::Parse(format!("char {} uses missing page {}", id, page))
+                    })?;
+                    let [x, y, width, height] = ["x", "y", "width", "height"]
+                        .map(|key| number(&attrs, tag, key).map(|n| n.max(0) as u32));
+                    let image = crop(page, x?, y?, width?, height?).ok_or_else(|| {
+                        FontError::Parse(format!("char {} is outside its page", id))
+                    })?;
+                    let glyph = BitmapChar {
+                        image,
+                        x_offset: number(&attrs, tag, "xoffset")?,
+                        y_offset: number(&attrs, tag, "yoffset")?,
+                        advance: number(&attrs, tag, "xadvance")?,
+                    };
+                    chars.insert(c, glyph);
+                }
+                "kerning" => {
+                    let first = char::from_u32(number(&attrs, tag, "first")? as u32);
+                    let second = char::from_u32(number(&attrs, tag, "second")? as u32);
+                    if let (Some(first), Some(second)) = (first, second) {
+                        kernings.insert((first, second), number(&attrs, tag, "amount")?);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let (line_height, base) =
+            common.ok_or_else(|| FontError::Parse("missing 'common' line".to_string()))?;
+        Ok(Self {
+            name,
+            size: size.filter(|&size| size > 0).unwrap_or(line_height),
+            line_height,
+            base,
+            chars,
+            kernings,
+        })
+    }
+
+    /// Cut a sheet into `cell_width` x `cell_height` cells, assigned to
+    /// `chars` left to right, top to bottom.
+    pub fn from_grid(
+        sheet: &RgbaImage,
+        cell_width: u32,
+        cell_height: u32,
+        chars: &str,
+    ) -> Result<Self, FontError> {
+        let columns = sheet.width().checked_div(cell_width).unwrap_or(0);
+        if columns == 0 || cell_height == 0 {
+            return Err(FontError::Parse(format!(
+                "{}x{} cells don't fit a {}x{} sheet",
+                cell_width,
+                cell_height,
+                sheet.width(),
+                sheet.height()
+            )));
+        }
+
+        let mut glyphs = HashMap::new();
+        for (i, c) in chars.chars().enumerate() {
+            let (column, row) = (i as u32 % columns, i as u32 / columns);
+            let image = crop(
+                sheet,
+                column * cell_width,
+                row * cell_height,
+                cell_width,
+                cell_height,
+            )
+            .ok_or_else(|| {
+                FontError::Parse(format!("the sheet has no cell for '{}' (char {})", c, i))
+            })?;
+            let glyph = BitmapChar {
+                image,
+                x_offset: 0,
+                y_offset: 0,
+                advance: cell_width as i32,
+            };
+            glyphs.insert(c, glyph);
+        }
+
+        Ok(Self {
+            name: None,
+            size: cell_height,
+            line_height: cell_height,
+            base: cell_height,
+            chars: glyphs,
+            kernings: HashMap::new(),
+        })
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.chars.contains_key(&c)
+    }
+
+    /// The glyph for `c` at `size`. Characters the font lacks are blank and
+    /// as wide as a space, so text keeps its shape.
+    pub fn rasterize(&self, c: char, size: f32) -> Glyph {
+        let scale = self.scale(size);
+        let Some(glyph) = self.chars.get(&c) else {
+            let advance = self
+                .chars
+                .get(&' ')
+                .map_or(self.line_height as i32 / 2, |space| space.advance);
+            let metrics = Metrics {
+                advance_width: (advance * scale as i32) as f32,
+                ..Metrics::default()
+            };
+            return Glyph {
+                metrics,
+                bitmap: Vec::new(),
+                pixels: None,
+            };
+        };
+
+        let (width, height) = (glyph.image.width() * scale, glyph.image.height() * scale);
+        let image = if scale == 1 {
+            glyph.image.clone()
+        } else {
+            imageops::resize(&glyph.image, width, height, FilterType::Nearest)
+        };
+        let scale = scale as i32;
+        let metrics = Metrics {
+            xmin: glyph.x_offset * scale,
+            // Distance from the baseline up to the bottom of the image
+            ymin: (self.base as i32 - glyph.y_offset) * scale - height as i32,
+            width: width as usize,
+            height: height as usize,
+            advance_width: (glyph.advance * scale) as f32,
+            ..Metrics::default()
+        };
+        let bitmap = image.pixels().map(|p| p.0[3]).collect();
+        Glyph {
+            metrics,
+            bitmap,
+            pixels: Some(image),
+        }
+    }
+
+    /// Adjustment to the advance of `left` when `right` follows it.
+    pub fn kerning(&self, left: char, right: char, size: f32) -> f32 {
+        let amount = self.kernings.get(&(left, right)).copied().unwrap_or(0);
+        (amount * self.scale(size) as i32) as f32
+    }
+
+    /// Ascent, descent and line spacing at `size`.
+    pub fn line_metrics(&self, size: f32) -> LineMetrics {
+        let scale = self.scale(size) as f32;
+        LineMetrics {
+            ascent: self.base as f32 * scale,
+            descent: -(self.line_height.saturating_sub(self.base) as f32) * scale,
+            line_gap: 0.0,
+            new_line_size: self.line_height as f32 * scale,
+        }
+    }
+
+    /// The whole-number scale closest to `size`, at least 1.
+    fn scale(&self, size: f32) -> u32 {
+        (size / self.size as f32).round().max(1.0) as u32
+    }
+}
+
+/// Split a BMFont line into its tag and `key=value` attributes.
+/// Values may be quoted to contain spaces.
+fn parse_line(line: &str) -> (&str, HashMap<&str, &str>) {
+    let line = line.trim();
+    let (tag, mut rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let mut attrs = HashMap::new();
+    while let Some((key, after)) = rest.trim_start().split_once('=') {
+        let (value, remaining) = match after.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => after.split_once(char::is_whitespace).unwrap_or((after, "")),
+        };
+        attrs.insert(key, value);
+        rest = remaining;
+    }
+    (tag, attrs)
+}
+
+/// A required integer attribute of a BMFont line.
+fn number(attrs: &HashMap<&str, &str>, tag: &str, key: &str) -> Result<i32, FontError> {
+    let value = attrs
+        .get(key)
+        .ok_or_else(|| FontError::Parse(format!("'{}' line is missing '{}'", tag, key)))?;
+    value
+        .parse()
+        .map_err(|_| FontError::Parse(format!("'{}' {}={} is not a number", tag, key, value)))
+}
+
+/// Copy a sub-rectangle of `image`, or `None` if it doesn't fit.
+fn crop(image: &RgbaImage, x: u32, y: u32, width: u32, height: u32) -> Option<RgbaImage> {
+    let fits_x = x
+        .checked_add(width)
+        .is_some_and(|right| right <= image.width());
+    let fits_y = y
+        .checked_add(height)
+        .is_some_and(|bottom| bottom <= image.height());
+    (fits_x && fits_y).then(|| imageops::crop_imm(image, x, y, width, height).to_image())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    const FNT: &str = r#"info face="Tiny Pixel" size=4 bold=0
+common lineHeight=5 base=4 scaleW=8 scaleH=4 pages=1
+page id=0 file="tiny_0.png"
+chars count=2
+char id=65 x=0 y=0 width=3 height=4 xoffset=0 yoffset=0 xadvance=4 page=0 chnl=15
+char id=32 x=0 y=0 width=0 height=0 xoffset=0 yoffset=4 xadvance=2 page=0 chnl=15
+"#;
+
+    #[test]
+    fn test_bmfont_glyphs_scale_by_whole_pixels() {
+        let font = BitmapFont::parse_bmfont(FNT, |file| {
+            assert_eq!(file, "tiny_0.png");
+            Ok(RgbaImage::from_pixel(8, 4, Rgba([255, 0, 0, 255])))
+        })
+        .unwrap();
+        assert_eq!(font.name(), Some("Tiny Pixel"));
+        assert!(font.has_glyph('A') && !font.has_glyph('B'));
+
+        // 7px rounds to a 2x scale
+        let glyph = font.rasterize('A', 7.0);
+        assert_eq!((glyph.metrics.width, glyph.metrics.height), (6, 8));
+        assert_eq!((glyph.metrics.ymin, glyph.metrics.advance_width), (0, 8.0));
+        assert_eq!(
+            glyph.pixels.unwrap().get_pixel(5, 7),
+            &Rgba([255, 0, 0, 255])
+        );
+        assert_eq!(font.line_metrics(7.0).new_line_size, 10.0);
+
+        // Unknown characters advance like a space
+        assert_eq!(font.rasterize('B', 4.0).metrics.advance_width, 2.0);
+    }
+
+    #[test]
+    fn test_grid_sheet_assigns_cells_row_by_row() {
+        let mut sheet = RgbaImage::new(4, 4);
+        sheet.put_pixel(0, 2, Rgba([0, 0, 255, 255]));
+        let font = BitmapFont::from_grid(&sheet, 2, 2, "abc").unwrap();
+
+        let c = font.rasterize('c', 2.0);
+        assert_eq!(c.pixels.unwrap().get_pixel(0, 0), &Rgba([0, 0, 255, 255]));
+        assert_eq!(c.metrics.advance_width, 2.0);
+        assert!(BitmapFont::from_grid(&sheet, 2, 2, "abcde").is_err());
+        assert!(BitmapFont::from_grid(&sheet, 0, 2, "a").is_err());
+    }
+}
@@ -0,0 +1,226 @@
+//! Shadows, outlines and glows drawn behind text.
+
+use crate::core::Rect;
+use crate::graphics::Canvas;
+
+use super::{glyph_position, ShapedGlyph};
+
+/// A copy of the text drawn offset behind it, optionally blurred.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextShadow {
+    pub offset: (i32, i32),
+    pub color: u32,
+    /// Blur radius in pixels (0 for a hard shadow).
+    pub blur: u32,
+}
+
+/// A stroke of `width` pixels around every glyph.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextOutline {
+    pub width: u32,
+    pub color: u32,
+}
+
+/// A soft halo fading out over `radius` pixels around the text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextGlow {
+    pub radius: u32,
+    pub color: u32,
+}
+
+/// The effects drawn behind a piece of text: glow first, then the shadow,
+/// then the outline, with the text itself on top.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TextEffects {
+    pub shadow: Option<TextShadow>,
+    pub outline: Option<TextOutline>,
+    pub glow: Option<TextGlow>,
+}
+
+impl TextEffects {
+    pub fn is_empty(&self) -> bool {
+        self.shadow.is_none() && self.outline.is_none() && self.glow.is_none()
+    }
+
+    /// How far the effects reach past the glyphs, ignoring the shadow offset.
+    fn margin(&self) -> u32 {
+        let outline = self.outline.map_or(0, |o| o.width);
+        let shadow = self.shadow.map_or(0, |s| s.blur);
+        let glow = self.glow.map_or(0, |g| g.radius + g.radius / 2);
+        (outline + shadow).max(glow) + 1
+    }
+}
+
+/// Draw `effects` for glyphs placed as `draw_text_sized` places them.
+pub(super) fn draw_effects(
+    canvas: &mut Canvas,
+    glyphs: &[ShapedGlyph],
+    x: f32,
+    baseline_y: f32,
+    clip: Option<&Rect>,
+    effects: &TextEffects,
+) {
+    if effects.is_empty() {
+        return;
+    }
+    let Some(mask) = Mask::from_glyphs(glyphs, x, baseline_y, effects.margin()) else {
+        return;
+    };
+
+    if let Some(glow) = effects.glow {
+        let halo = mask.dilated(glow.radius / 2).blurred(glow.radius);
+        halo.blend(canvas, (0, 0), clip, glow.color);
+    }
+
+    let outlined = effects.outline.map(|outline| mask.dilated(outline.width));
+    if let Some(shadow) = effects.shadow {
+        // The shadow is cast by the outlined text when there is an outline
+        let caster = outlined.as_ref().unwrap_or(&mask);
+        caster
+            .blurred(shadow.blur)
+            .blend(canvas, shadow.offset, clip, shadow.color);
+    }
+    if let (Some(outline), Some(outlined)) = (effects.outline, &outlined) {
+        outlined.blend(canvas, (0, 0), clip, outline.color);
+    }
+}
+
+/// Coverage of rendered text, with its top-left corner on the canvas.
+#[derive(Clone)]
+struct Mask {
+    x: i32,
+    y: i32,
+    width: usize,
+    height: usize,
+    alpha: Vec<u8>,
+}
+
+impl Mask {
+    /// Rasterize the glyphs' coverage, with `margin` empty pixels on each side.
+    fn from_glyphs(glyphs: &[ShapedGlyph], x: f32, baseline_y: f32, margin: u32) -> Option<Self> {
+        let placed: Vec<_> = glyphs
+            .iter()
+            .filter(|shaped| !shaped.glyph.bitmap.is_empty())
+            .map(|shaped| (glyph_position(shaped, x, baseline_y), &shaped.glyph))
+            .collect();
+        let left = placed.iter().map(|((gx, _), _)| *gx).min()?;
+        let top = placed.iter().map(|((_, gy), _)| *gy).min()?;
+        let right = placed
+            .iter()
+            .map(|((gx, _), g)| gx + g.metrics.width as i32)
+            .max()?;
+        let bottom = placed
+            .iter()
+            .map(|((_, gy), g)| gy + g.metrics.height as i32)
+            .max()?;
+
+        let margin = margin as i32;
+        let (origin_x, origin_y) = (left - margin, top - margin);
+        let width = (right - left + 2 * margin) as usize;
+        let height = (bottom - top + 2 * margin) as usize;
+        let mut alpha = vec![0u8; width * height];
+
+        for ((gx, gy), glyph) in placed {
+            let (glyph_width, glyph_height) = (glyph.metrics.width, glyph.metrics.height);
+            let (dx, dy) = ((gx - origin_x) as usize, (gy - origin_y) as usize);
+            for row in 0..glyph_height {
+                for col in 0..glyph_width {
+                    let coverage = glyph.bitmap[row * glyph_width + col];
+                    let cell = &mut alpha[(dy + row) * width + dx + col];
+                    *cell = (*cell).max(coverage);
+                }
+            }
+        }
+
+        Some(Self {
+            x: origin_x,
+            y: origin_y,
+            width,
+            height,
+            alpha,
+        })
+    }
+
+    /// Grow the coverage by `radius` pixels, anti-aliased at the edge.
+    fn dilated(&self, radius: u32) -> Self {
+        if radius == 0 {
+            return self.clone();
+        }
+        let reach = radius as i32 + 1;
+        let kernel: Vec<(i32, i32, f32)> = (-reach..=reach)
+            .flat_map(|dy| (-reach..=reach).map(move |dx| (dx, dy)))
+            .filter_map(|(dx, dy)| {
+                let distance = ((dx * dx + dy * dy) as f32).sqrt();
+                let weight = (radius as f32 + 1.0 - distance).clamp(0.0, 1.0);
+                (weight > 0.0).then_some((dx, dy, weight))
+            })
+            .collect();
+
+        let mut alpha = vec![0u8; self.alpha.len()];
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                let mut best = 0.0f32;
+                for &(dx, dy, weight) in &kernel {
+                    best = best.max(self.get(x + dx, y + dy) as f32 * weight);
+                }
+                alpha[y as usize * self.width + x as usize] = best.round() as u8;
+            }
+        }
+        Self {
+            alpha,
+            ..self.clone()
+        }
+    }
+
+    /// Soften the coverage with two box blur passes of half `radius` each,
+    /// which together approximate a gaussian reaching `radius` pixels.
+    fn blurred(&self, radius: u32) -> Self {
+        let mut mask = self.clone();
+        let pass_radius = radius.div_ceil(2) as i32;
+        if pass_radius == 0 {
+            return mask;
+        }
+        for _ in 0..2 {
+            mask.alpha = mask.box_blur(pass_radius, 1, 0);
+            mask.alpha = mask.box_blur(pass_radius, 0, 1);
+        }
+        mask
+    }
+
+    /// Average every pixel with its neighbours within `radius` along (`step_x`, `step_y`).
+    fn box_blur(&self, radius: i32, step_x: i32, step_y: i32) -> Vec<u8> {
+        let span = (2 * radius + 1) as u32;
+        let mut alpha = vec![0u8; self.alpha.len()];
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                let sum: u32 = (-radius..=radius)
+                    .map(|i| self.get(x + i * step_x, y + i * step_y) as u32)
+                    .sum();
+                alpha[y as usize * self.width + x as usize] = (sum / span) as u8;
+            }
+        }
+        alpha
+    }
+
+    fn get(&self, x: i32, y: i32) -> u8 {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return 0;
+        }
+        self.alpha[y as usize * self.width + x as usize]
+    }
+
+    /// Composite the mask in `color`, shifted by `offset`.
+    fn blend(&self, canvas: &mut Canvas, offset: (i32, i32), clip: Option<&Rect>, color: u32) {
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let alpha = self.alpha[row * self.width + col];
+                let px = self.x + offset.0 + col as i32;
+                let py = self.y + offset.1 + row as i32;
+                if alpha == 0 || px < 0 || py < 0 || clip.is_some_and(|c| !c.contains(px, py)) {
+                    continue;
+                }
+                canvas.blend_pixel(px as u32, py as u32, color, alpha);
+            }
+        }
+    }
+}
@@ -0,0 +1,502 @@
+//! Loaded fonts and the registry skins look them up in.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+
+use fontdue::{FontSettings, LineMetrics};
+use rustybuzz::{Direction, UnicodeBuffer};
+use unicode_bidi::{bidi_class, BidiClass};
+
+use super::bitmap::BitmapFont;
+use super::glyph_cache::{Glyph, GlyphCache, DEFAULT_GLYPH_CAPACITY};
+use super::shaping::{ShapeCache, ShapedText};
+use super::FontError;
+
+/// Registry key of the font used when a part doesn't name one.
+pub const DEFAULT_FONT: &str = "default";
+
+/// Font size used when neither the part nor the bundle sets one.
+pub const DEFAULT_FONT_SIZE: f32 = 16.0;
+
+/// Where a face's glyphs come from.
+enum FaceSource {
+    /// TTF/OTF outlines, rasterized at any size, and the file they came
+    /// from for the shaper.
+    Outline {
+        font: fontdue::Font,
+        data: Arc<[u8]>,
+    },
+    /// Pre-drawn glyph images.
+    Bitmap(BitmapFont),
+}
+
+/// A glyph placed by a face's shaper, relative to the start of its run.
+pub(super) struct FaceGlyph {
+    pub glyph: Arc<Glyph>,
+    /// Byte offset in the run of the first character the glyph belongs to.
+    pub cluster: usize,
+    pub advance: f32,
+    /// Shift from the pen position, with y pointing up.
+    pub offset: (f32, f32),
+}
+
+/// Fallback faces of a chain that starts with some face, and the strings
+/// shaped with that chain.
+type ChainShapes = (Vec<Weak<FontFace>>, Arc<Mutex<ShapeCache>>);
+
+/// A parsed font together with the cache of its rasterized glyphs.
+pub(super) struct FontFace {
+    source: FaceSource,
+    glyphs: Mutex<GlyphCache>,
+    /// Shape caches of the chains this face starts, so every part using the
+    /// same font shares its shaped strings.
+    shapes: Mutex<Vec<ChainShapes>>,
+}
+
+impl FontFace {
+    fn new(source: FaceSource) -> Self {
+        Self {
+            source,
+            glyphs: Mutex::new(GlyphCache::new(DEFAULT_GLYPH_CAPACITY)),
+            shapes: Mutex::default(),
+        }
+    }
+
+    /// The shape cache of the chain made of this face and `fallbacks`.
+    fn shape_cache(&self, fallbacks: &[Arc<FontFace>]) -> Arc<Mutex<ShapeCache>> {
+        let mut chains = self.shapes.lock().unwrap_or_else(PoisonError::into_inner);
+        // Chains with a dropped face can't be built again
+        chains.retain(|(faces, _)| faces.iter().all(|face| face.strong_count() > 0));
+
+        let same_faces = |faces: &[Weak<FontFace>]| {
+            faces.len() == fallbacks.len()
+                && faces
+                    .iter()
+                    .zip(fallbacks)
+                    .all(|(a, b)| Weak::as_ptr(a) == Arc::as_ptr(b))
+        };
+        if let Some((_, shapes)) = chains.iter().find(|(faces, _)| same_faces(faces)) {
+            return Arc::clone(shapes);
+        }
+        let shapes = Arc::default();
+        chains.push((
+            fallbacks.iter().map(Arc::downgrade).collect(),
+            Arc::clone(&shapes),
+        ));
+        shapes
+    }
+
+    fn has_glyph(&self, c: char) -> bool {
+        match &self.source {
+            FaceSource::Outline { font, .. } => font.has_glyph(c),
+            FaceSource::Bitmap(font) => font.has_glyph(c),
+        }
+    }
+
+    fn glyph(&self, c: char, size: f32) -> Arc<Glyph> {
+        match &self.source {
+            FaceSource::Outline { font, .. } => {
+                self.outline_glyph(font, font.lookup_glyph_index(c), size)
+            }
+            FaceSource::Bitmap(font) => self.bitmap_glyph(font, c, size),
+        }
+    }
+
+    /// Outline glyphs are cached by glyph index, since shaping may pick
+    /// glyphs (ligatures, contextual forms) no single character maps to.
+    fn outline_glyph(&self, font: &fontdue::Font, index: u16, size: f32) -> Arc<Glyph> {
+        let mut glyphs = self.glyphs.lock().unwrap_or_else(PoisonError::into_inner);
+        glyphs.get(index as u32, size, || {
+            let (metrics, bitmap) = font.rasterize_indexed(index, size);
+            Glyph {
+                metrics,
+                bitmap,
+                pixels: None,
+            }
+        })
+    }
+
+    /// Bitmap glyphs are cached by character.
+    fn bitmap_glyph(&self, font: &BitmapFont, c: char, size: f32) -> Arc<Glyph> {
+        let mut glyphs = self.glyphs.lock().unwrap_or_else(PoisonError::into_inner);
+        glyphs.get(c as u32, size, || font.rasterize(c, size))
+    }
+
+    /// Shape a run of text that has a single direction, returning its
+    /// glyphs in visual (left to right) order.
+    pub(super) fn shape(&self, text: &str, rtl: bool, size: f32) -> Vec<FaceGlyph> {
+        match &self.source {
+            FaceSource::Outline { font, data } => {
+                // Checked when the font was loaded; parsing only reads the table directory
+                let Some(shaper) = rustybuzz::Face::from_slice(data, 0) else {
+                    return Vec::new();
+                };
+                let scale = size / shaper.units_per_em() as f32;
+                let mut buffer = UnicodeBuffer::new();
+                buffer.push_str(text);
+                buffer.set_direction(if rtl {
+                    Direction::RightToLeft
+                } else {
+                    Direction::LeftToRight
+                });
+                let output = rustybuzz::shape(&shaper, &[], buffer);
+                output
+                    .glyph_infos()
+                    .iter()
+                    .zip(output.glyph_positions())
+                    .map(|(info, position)| FaceGlyph {
+                        glyph: self.outline_glyph(font, info.glyph_id as u16, size),
+                        cluster: info.cluster as usize,
+                        advance: position.x_advance as f32 * scale,
+                        offset: (
+                            position.x_offset as f32 * scale,
+                            position.y_offset as f32 * scale,
+                        ),
+                    })
+                    .collect()
+            }
+            FaceSource::Bitmap(font) => {
+                let mut chars: Vec<_> = text.char_indices().collect();
+                if rtl {
+                    chars.reverse();
+                }
+                let mut glyphs: Vec<FaceGlyph> = Vec::with_capacity(chars.len());
+                let mut previous = None;
+                for (cluster, c) in chars {
+                    if let (Some(left), Some(last)) = (previous, glyphs.last_mut()) {
+                        last.advance += font.kerning(left, c, size);
+                    }
+                    let glyph = self.bitmap_glyph(font, c, size);
+                    let advance = glyph.metrics.advance_width;
+                    glyphs.push(FaceGlyph {
+                        glyph,
+                        cluster,
+                        advance,
+                        offset: (0.0, 0.0),
+                    });
+                    previous = Some(c);
+                }
+                glyphs
+            }
+        }
+    }
+
+    fn line_metrics(&self, size: f32) -> Option<LineMetrics> {
+        match &self.source {
+            FaceSource::Outline { font, .. } => font.horizontal_line_metrics(size),
+            FaceSource::Bitmap(font) => Some(font.line_metrics(size)),
+        }
+    }
+
+    fn name(&self) -> Option<&str> {
+        match &self.source {
+            FaceSource::Outline { font, .. } => font.name(),
+            FaceSource::Bitmap(font) => font.name(),
+        }
+    }
+}
+
+/// A cheaply cloneable handle to a loaded font and its fallbacks.
+///
+/// Each character is drawn with the first face in the chain that has a
+/// glyph for it. The default font has no faces: it measures text as
+/// zero-width and draws nothing, so widgets without a font stay usable.
+#[derive(Clone, Default)]
+pub struct Font {
+    faces: Vec<Arc<FontFace>>,
+    /// Strings already shaped with this chain of faces, shared by every
+    /// font with the same chain.
+    shapes: Arc<Mutex<ShapeCache>>,
+}
+
+impl Font {
+    /// Load a TTF/OTF font file, or a BMFont `.fnt` file and its pages.
+    pub fn load(path: &Path) -> Result<Self, FontError> {
+        let is_bmfont = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("fnt"));
+        if is_bmfont {
+            return Ok(Self::from_bitmap(BitmapFont::load_bmfont(path)?));
+        }
+        let data = std::fs::read(path).map_err(FontError::Io)?;
+        Self::from_bytes(data)
+    }
+
+    /// Parse a font from the contents of a TTF/OTF file.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, FontError> {
+        let font = fontdue::Font::from_bytes(data.as_slice(), FontSettings::default())
+            .map_err(|e| FontError::Parse(e.to_string()))?;
+        if rustybuzz::Face::from_slice(&data, 0).is_none() {
+            return Err(FontError::Parse("the font can't be shaped".to_string()));
+        }
+        let face = FontFace::new(FaceSource::Outline {
+            font,
+            data: data.into(),
+        });
+        Ok(Self::from_faces(vec![Arc::new(face)]))
+    }
+
+    /// Wrap a bitmap font.
+    pub fn from_bitmap(font: BitmapFont) -> Self {
+        let face = FontFace::new(FaceSource::Bitmap(font));
+        Self::from_faces(vec![Arc::new(face)])
+    }
+
+    /// This font followed by the faces of `fallback` it doesn't already use.
+    pub fn with_fallback(mut self, fallback: &Font) -> Self {
+        let mut faces = self.faces.clone();
+        for face in &fallback.faces {
+            if !faces.iter().any(|f| Arc::ptr_eq(f, face)) {
+                faces.push(Arc::clone(face));
+            }
+        }
+        // Text shaped without the fallbacks may now come out differently
+        if faces.len() != self.faces.len() {
+            self = Self::from_faces(faces);
+        }
+        self
+    }
+
+    fn from_faces(faces: Vec<Arc<FontFace>>) -> Self {
+        let shapes = match faces.split_first() {
+            Some((first, fallbacks)) => first.shape_cache(fallbacks),
+            None => Arc::default(),
+        };
+        Self { faces, shapes }
+    }
+
+    /// `text` shaped at `size`, calling `shape` only if it isn't cached.
+    pub(super) fn shaped(
+        &self,
+        text: &str,
+        size: f32,
+        shape: impl FnOnce() -> ShapedText,
+    ) -> Arc<ShapedText> {
+        let mut shapes = self.shapes.lock().unwrap_or_else(PoisonError::into_inner);
+        shapes.get(text, size, shape)
+    }
+
+    /// Whether any face in the chain has a glyph for `c`.
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.faces.iter().any(|face| face.has_glyph(c))
+    }
+
+    /// The rasterized glyph for `c` at `size`, from the cache when possible.
+    /// Characters no face covers use the first face's missing-glyph box.
+    pub fn glyph(&self, c: char, size: f32) -> Option<Arc<Glyph>> {
+        let face = self
+            .faces
+            .iter()
+            .find(|face| face.has_glyph(c))
+            .or(self.faces.first())?;
+        Some(face.glyph(c, size))
+    }
+
+    /// Split `text` into runs drawn by a single face: each character goes
+    /// to the first face with a glyph for it, and combining marks stay with
+    /// the character they modify.
+    pub(super) fn itemize(&self, text: &str) -> Vec<(&FontFace, Range<usize>)> {
+        let mut runs: Vec<(&FontFace, Range<usize>)> = Vec::new();
+        for (i, c) in text.char_indices() {
+            let end = i + c.len_utf8();
+            let face = match runs.last() {
+                Some((face, _)) if bidi_class(c) == BidiClass::NSM => *face,
+                _ => match self
+                    .faces
+                    .iter()
+                    .find(|f| f.has_glyph(c))
+                    .or(self.faces.first())
+                {
+                    Some(face) => face.as_ref(),
+                    None => break,
+                },
+            };
+            match runs.last_mut() {
+                Some((last, range)) if std::ptr::eq(*last, face) => range.end = end,
+                _ => runs.push((face, i..end)),
+            }
+        }
+        runs
+    }
+
+    /// Horizontal advance of `c` at `size`.
+    pub fn advance(&self, c: char, size: f32) -> f32 {
+        self.glyph(c, size).map_or(0.0, |g| g.metrics.advance_width)
+    }
+
+    /// Ascent, descent and line spacing at `size`.
+    pub fn line_metrics(&self, size: f32) -> LineMetrics {
+        self.faces
+            .first()
+            .and_then(|face| face.line_metrics(size))
+            .unwrap_or(LineMetrics {
+                ascent: size,
+                descent: 0.0,
+                line_gap: 0.0,
+                new_line_size: size,
+            })
+    }
+}
+
+impl std::fmt::Debug for Font {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<_> = self.faces.iter().map(|face| face.name()).collect();
+        f.debug_struct("Font").field("faces", &names).finish()
+    }
+}
+
+/// Fonts available to a skin, by key (e.g. "default", "title", "mono").
+#[derive(Debug, Clone)]
+pub struct FontRegistry {
+    fonts: HashMap<String, Font>,
+    /// Keys of fonts tried, in order, for characters a font lacks.
+    fallbacks: Vec<String>,
+    default_size: f32,
+}
+
+impl FontRegistry {
+    pub fn new() -> Self {
+        Self {
+            fonts: HashMap::new(),
+            fallbacks: Vec::new(),
+            default_size: DEFAULT_FONT_SIZE,
+        }
+    }
+
+    /// Register a font under `key`, replacing any font with that key.
+    pub fn insert(&mut self, key: impl Into<String>, font: Font) {
+        self.fonts.insert(key.into(), font);
+    }
+
+    /// Load a font file (see `Font::load`) and register it under `key`.
+    pub fn load(&mut self, key: impl Into<String>, path: &Path) -> Result<(), FontError> {
+        let font = Font::load(path)?;
+        self.insert(key, font);
+        Ok(())
+    }
+
+    /// The font registered under `key`, without fallbacks.
+    pub fn get(&self, key: &str) -> Option<&Font> {
+        self.fonts.get(key)
+    }
+
+    /// The font registered under `key`, followed by the fallback fonts.
+    pub fn resolve(&self, key: &str) -> Option<Font> {
+        let font = self.fonts.get(key)?.clone();
+        Some(
+            self.fallback_fonts()
+                .fold(font, |font, fallback| font.with_fallback(fallback)),
+        )
+    }
+
+    /// Set the keys of the fonts tried for characters a font lacks.
+    pub fn set_fallbacks(&mut self, keys: Vec<String>) {
+        self.fallbacks = keys;
+    }
+
+    pub fn fallbacks(&self) -> &[String] {
+        &self.fallbacks
+    }
+
+    fn fallback_fonts(&self) -> impl Iterator<Item = &Font> {
+        self.fallbacks.iter().filter_map(|key| self.fonts.get(key))
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.fonts.contains_key(key)
+    }
+
+    /// The font registered as `DEFAULT_FONT`, followed by the fallbacks.
+    pub fn default_font(&self) -> Option<Font> {
+        self.resolve(DEFAULT_FONT)
+    }
+
+    /// Size used for text that doesn't set its own.
+    pub fn default_size(&self) -> f32 {
+        self.default_size
+    }
+
+    pub fn set_default_size(&mut self, size: f32) {
+        self.default_size = size;
+    }
+}
+
+impl Default for FontRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::text::shaping::shape_text;
+
+    #[test]
+    fn test_registries_hold_independent_fonts() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let mut demo = FontRegistry::new();
+        demo.load(DEFAULT_FONT, &root.join("demo_app.crix/skin/font.ttf"))
+            .unwrap();
+        let mut runner = FontRegistry::new();
+        runner
+            .load(DEFAULT_FONT, &root.join("crix_runner.crix/skin/font.ttf"))
+            .unwrap();
+        runner.insert("title", demo.default_font().unwrap());
+
+        assert!(demo.default_font().unwrap().advance('W', 16.0) > 0.0);
+        assert!(runner.default_font().unwrap().advance('W', 16.0) > 0.0);
+        assert!(runner.contains("title"));
+        assert!(!demo.contains("title"));
+        assert_eq!(Font::default().advance('W', 16.0), 0.0);
+    }
+
+    #[test]
+    fn test_fallback_fonts_cover_missing_glyphs() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("demo_app.crix/skin/font.ttf");
+        let mut fonts = FontRegistry::new();
+        fonts.insert("empty", Font::default());
+        fonts.load("pixel", &path).unwrap();
+        fonts.set_fallbacks(vec!["pixel".to_string(), "missing".to_string()]);
+
+        // The empty font has no glyphs of its own; every character comes from "pixel"
+        let font = fonts.resolve("empty").unwrap();
+        assert!(font.has_glyph('A'));
+        assert!(font.advance('A', 16.0) > 0.0);
+
+        // A font is never its own fallback twice, and unknown keys are skipped
+        let pixel = fonts.resolve("pixel").unwrap();
+        assert_eq!(pixel.faces.len(), 1);
+        assert!(!pixel.has_glyph('\u{4F60}'));
+        assert!(pixel.glyph('\u{4F60}', 16.0).is_some());
+    }
+
+    #[test]
+    fn test_fonts_with_the_same_faces_share_shaped_text() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let mut fonts = FontRegistry::new();
+        fonts
+            .load("pixel", &root.join("demo_app.crix/skin/font.ttf"))
+            .unwrap();
+        fonts
+            .load("runner", &root.join("crix_runner.crix/skin/font.ttf"))
+            .unwrap();
+        let shape = |font: &Font| shape_text(font, "shared", 16.0);
+
+        // Every part resolving "pixel" gets the same cache
+        let first = shape(&fonts.resolve("pixel").unwrap());
+        assert!(Arc::ptr_eq(
+            &first,
+            &shape(&fonts.resolve("pixel").unwrap())
+        ));
+
+        // A different chain of faces may shape the text differently
+        fonts.set_fallbacks(vec!["runner".to_string()]);
+        assert!(!Arc::ptr_eq(
+            &first,
+            &shape(&fonts.resolve("pixel").unwrap())
+        ));
+    }
+}
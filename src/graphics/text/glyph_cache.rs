@@ -0,0 +1,92 @@
+//! Cache of rasterized glyphs so text isn't re-rasterized every frame.
+
+use std::sync::Arc;
+
+use fontdue::Metrics;
+use image::RgbaImage;
+
+use super::lru::Lru;
+
+/// Glyphs kept per font before the least recently used are evicted.
+pub const DEFAULT_GLYPH_CAPACITY: usize = 2048;
+
+/// A rasterized glyph: its metrics and coverage bitmap.
+#[derive(Debug)]
+pub struct Glyph {
+    pub metrics: Metrics,
+    /// `metrics.width * metrics.height` coverage values, row by row.
+    pub bitmap: Vec<u8>,
+    /// Colored pixels of bitmap font glyphs, drawn as-is instead of in the text color.
+    pub pixels: Option<RgbaImage>,
+}
+
+/// Glyphs are keyed by an id (glyph index or character, depending on the
+/// font) and font size (as bits, so any size works).
+type GlyphKey = (u32, u32);
+
+/// An LRU-bounded cache of rasterized glyphs for one font.
+#[derive(Debug)]
+pub struct GlyphCache {
+    entries: Lru<GlyphKey, Arc<Glyph>>,
+}
+
+impl GlyphCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Lru::new(capacity),
+        }
+    }
+
+    /// Get glyph `id` at `size`, calling `rasterize` on a miss.
+    pub fn get(&mut self, id: u32, size: f32, rasterize: impl FnOnce() -> Glyph) -> Arc<Glyph> {
+        let key = (id, size.to_bits());
+        Arc::clone(
+            self.entries
+                .get_or_insert_with(key, || Arc::new(rasterize())),
+        )
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fontdue::{Font, FontSettings};
+    use std::path::Path;
+
+    fn get(cache: &mut GlyphCache, font: &Font, c: char) -> Arc<Glyph> {
+        cache.get(c as u32, 16.0, || {
+            let (metrics, bitmap) = font.rasterize(c, 16.0);
+            Glyph {
+                metrics,
+                bitmap,
+                pixels: None,
+            }
+        })
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("demo_app.crix/skin/font.ttf");
+        let font = Font::from_bytes(std::fs::read(path).unwrap(), FontSettings::default()).unwrap();
+        let mut cache = GlyphCache::new(2);
+
+        let a = get(&mut cache, &font, 'a');
+        get(&mut cache, &font, 'b');
+        assert!(Arc::ptr_eq(&a, &get(&mut cache, &font, 'a')));
+
+        // 'b' is now the least recently used and makes room for 'c'
+        get(&mut cache, &font, 'c');
+        assert_eq!(cache.len(), 2);
+        assert!(Arc::ptr_eq(&a, &get(&mut cache, &font, 'a')));
+        assert!(cache.entries.contains_key(&('c' as u32, 16.0f32.to_bits())));
+        assert!(!cache.entries.contains_key(&('b' as u32, 16.0f32.to_bits())));
+    }
+}
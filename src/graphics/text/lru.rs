@@ -0,0 +1,143 @@
+//! A bounded map that evicts the least recently used entry, shared by the
+//! glyph and shape caches.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// An entry, linked to the ones used just before and after it.
+#[derive(Debug)]
+struct Slot<K, V> {
+    key: K,
+    value: V,
+    /// More recently used neighbour.
+    newer: Option<usize>,
+    /// Less recently used neighbour.
+    older: Option<usize>,
+}
+
+/// A map holding at most `capacity` entries. Lookups, inserts and
+/// evictions are O(1): entries live in a slab linked from most to least
+/// recently used, and a full map reuses the slot of the one it evicts.
+#[derive(Debug)]
+pub(super) struct Lru<K, V> {
+    capacity: usize,
+    index: HashMap<K, usize>,
+    slots: Vec<Slot<K, V>>,
+    newest: Option<usize>,
+    oldest: Option<usize>,
+}
+
+impl<K: Hash + Eq + Clone, V> Lru<K, V> {
+    pub(super) fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            index: HashMap::new(),
+            slots: Vec::new(),
+            newest: None,
+            oldest: None,
+        }
+    }
+
+    /// The value for `key`, calling `make` to create it on a miss. A full
+    /// map first evicts its least recently used entry.
+    pub(super) fn get_or_insert_with(&mut self, key: K, make: impl FnOnce() -> V) -> &V {
+        if let Some(&slot) = self.index.get(&key) {
+            self.touch(slot);
+            return &self.slots[slot].value;
+        }
+
+        let value = make();
+        let slot = match self.oldest {
+            Some(oldest) if self.slots.len() >= self.capacity => {
+                self.unlink(oldest);
+                let evicted = &mut self.slots[oldest];
+                self.index.remove(&evicted.key);
+                evicted.key = key.clone();
+                evicted.value = value;
+                oldest
+            }
+            _ => {
+                let slot = Slot {
+                    key: key.clone(),
+                    value,
+                    newer: None,
+                    older: None,
+                };
+                self.slots.push(slot);
+                self.slots.len() - 1
+            }
+        };
+        self.index.insert(key, slot);
+        self.push_newest(slot);
+        &self.slots[slot].value
+    }
+
+    #[cfg(test)]
+    pub(super) fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index.contains_key(key)
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Make `slot` the most recently used.
+    fn touch(&mut self, slot: usize) {
+        if self.newest != Some(slot) {
+            self.unlink(slot);
+            self.push_newest(slot);
+        }
+    }
+
+    fn unlink(&mut self, slot: usize) {
+        let (newer, older) = (self.slots[slot].newer, self.slots[slot].older);
+        match newer {
+            Some(newer) => self.slots[newer].older = older,
+            None => self.newest = older,
+        }
+        match older {
+            Some(older) => self.slots[older].newer = newer,
+            None => self.oldest = newer,
+        }
+    }
+
+    fn push_newest(&mut self, slot: usize) {
+        self.slots[slot].newer = None;
+        self.slots[slot].older = self.newest;
+        match self.newest {
+            Some(newest) => self.slots[newest].newer = Some(slot),
+            None => self.oldest = Some(slot),
+        }
+        self.newest = Some(slot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_least_recently_used_entry_is_evicted() {
+        let mut lru = Lru::new(3);
+        for key in ["a", "b", "c"] {
+            lru.get_or_insert_with(key.to_string(), || key.len());
+        }
+        // Using "a" again leaves "b" as the oldest, then "c"
+        assert_eq!(
+            *lru.get_or_insert_with("a".to_string(), || unreachable!()),
+            1
+        );
+
+        lru.get_or_insert_with("d".to_string(), || 4);
+        assert!(!lru.contains_key("b"));
+        lru.get_or_insert_with("e".to_string(), || 5);
+        assert!(!lru.contains_key("c"));
+
+        assert_eq!(lru.len(), 3);
+        assert!(["a", "d", "e"].iter().all(|key| lru.contains_key(*key)));
+    }
+}
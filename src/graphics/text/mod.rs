@@ -1,129 +1,77 @@
-use std::path::Path;
-use std::sync::OnceLock;
-
-use fontdue::{Font, FontSettings};
+mod bitmap;
+mod effects;
+mod font;
+mod glyph_cache;
+mod lru;
+mod shaping;
 
 use crate::core::Rect;
 use crate::graphics::Canvas;
 
-/// Global font instance.
-static FONT: OnceLock<Font> = OnceLock::new();
-static FONT_SIZE: OnceLock<f32> = OnceLock::new();
-
-/// Initialize the font system with a TTF file.
-/// Must be called before any text rendering.
-pub fn init_font(path: &Path, size: f32) -> Result<(), FontError> {
-    let font_data = std::fs::read(path).map_err(|e| FontError::Io(e))?;
-    let font = Font::from_bytes(font_data, FontSettings::default())
-        .map_err(|e| FontError::Parse(e.to_string()))?;
-
-    FONT.set(font).map_err(|_| FontError::AlreadyInitialized)?;
-    FONT_SIZE.set(size).map_err(|_| FontError::AlreadyInitialized)?;
-
-    Ok(())
-}
-
-/// Get the loaded font, panics if not initialized.
-fn get_font() -> &'static Font {
-    FONT.get().expect("Font not initialized. Call init_font() first.")
-}
-
-/// Get the font size.
-fn get_font_size() -> f32 {
-    *FONT_SIZE.get().expect("Font not initialized. Call init_font() first.")
-}
+pub use bitmap::BitmapFont;
+pub use effects::{TextEffects, TextGlow, TextOutline, TextShadow};
+pub use font::{Font, FontRegistry, DEFAULT_FONT, DEFAULT_FONT_SIZE};
+pub use glyph_cache::{Glyph, GlyphCache, DEFAULT_GLYPH_CAPACITY};
+pub use shaping::{shape_text, ShapedGlyph, ShapedText};
 
-/// Get the line height for the current font.
-pub fn line_height() -> u32 {
-    line_height_sized(get_font_size())
-}
-
-/// Get the line height for a specific font size.
-pub fn line_height_sized(size: f32) -> u32 {
-    let font = get_font();
-    let metrics = font.horizontal_line_metrics(size).unwrap_or(fontdue::LineMetrics {
-        ascent: size,
-        descent: 0.0,
-        line_gap: 0.0,
-        new_line_size: size,
-    });
-    metrics.new_line_size.ceil() as u32
+/// Get the line height of a font at a specific size.
+pub fn line_height_sized(font: &Font, size: f32) -> u32 {
+    font.line_metrics(size).new_line_size.ceil() as u32
 }
 
 /// Text style for rendering.
 #[derive(Debug, Clone, Copy)]
 pub struct TextStyle {
     pub color: u32,
+    /// Shadow, outline and glow drawn behind the text.
+    pub effects: TextEffects,
 }
 
 impl Default for TextStyle {
     fn default() -> Self {
-        Self { color: 0xFFFFFF } // White
+        Self::with_color(0xFFFFFF) // White
     }
 }
 
 impl TextStyle {
     pub fn with_color(color: u32) -> Self {
-        Self { color }
-    }
-}
-
-/// Measure the width of a string in pixels.
-pub fn measure_text(text: &str) -> (u32, u32) {
-    let font = get_font();
-    let size = get_font_size();
-
-    if text.is_empty() {
-        return (0, line_height());
+        Self {
+            color,
+            effects: TextEffects::default(),
+        }
     }
 
-    let mut width = 0.0;
-    for c in text.chars() {
-        let metrics = font.metrics(c, size);
-        width += metrics.advance_width;
+    /// Set the effects drawn behind the text.
+    pub fn with_effects(mut self, effects: TextEffects) -> Self {
+        self.effects = effects;
+        self
     }
-
-    (width.ceil() as u32, line_height())
 }
 
-/// Get the x offset of the caret at the given character index.
-pub fn caret_x(text: &str, cursor_index: usize) -> u32 {
-    caret_x_sized(text, cursor_index, get_font_size())
+/// Measure the width and line height of a string in pixels.
+pub fn measure_text(font: &Font, text: &str, size: f32) -> (u32, u32) {
+    let width = shape_text(font, text, size).width;
+    (width.ceil() as u32, line_height_sized(font, size))
 }
 
 /// Get the x offset of the caret at the given character index with a specific font size.
-pub fn caret_x_sized(text: &str, cursor_index: usize, size: f32) -> u32 {
-    let font = get_font();
-
-    let mut x = 0.0;
-    for (i, c) in text.chars().enumerate() {
-        if i >= cursor_index {
-            break;
-        }
-        let metrics = font.metrics(c, size);
-        x += metrics.advance_width;
-    }
-
-    x.ceil() as u32
+pub fn caret_x_sized(font: &Font, text: &str, cursor_index: usize, size: f32) -> u32 {
+    shape_text(font, text, size).caret_x(cursor_index).ceil() as u32
 }
 
-/// Draw text to the canvas at the given position.
-/// Clips rendering to the optional clip_rect.
-pub fn draw_text(
-    canvas: &mut Canvas,
-    x: i32,
-    y: i32,
-    clip_rect: Option<&Rect>,
-    text: &str,
-    style: TextStyle,
-) {
-    draw_text_sized(canvas, x, y, clip_rect, text, style, get_font_size())
+/// Get the caret x offset before every character and after the last one,
+/// i.e. `caret_x_sized(font, text, i, size)` for each `i` in `0..=char_count`.
+pub fn caret_positions_sized(font: &Font, text: &str, size: f32) -> Vec<u32> {
+    let shaped = shape_text(font, text, size);
+    shaped.carets().iter().map(|x| x.ceil() as u32).collect()
 }
 
 /// Draw text to the canvas at the given position with a specific font size.
 /// Clips rendering to the optional clip_rect.
+#[allow(clippy::too_many_arguments)]
 pub fn draw_text_sized(
     canvas: &mut Canvas,
+    font: &Font,
     x: i32,
     y: i32,
     clip_rect: Option<&Rect>,
@@ -131,33 +79,36 @@ pub fn draw_text_sized(
     style: TextStyle,
     size: f32,
 ) {
-    let font = get_font();
-
-    let mut cursor_x = x as f32;
-
     // Get baseline offset
-    let metrics = font.horizontal_line_metrics(size).unwrap_or(fontdue::LineMetrics {
-        ascent: size,
-        descent: 0.0,
-        line_gap: 0.0,
-        new_line_size: size,
-    });
-    let baseline_y = y as f32 + metrics.ascent;
-
-    for c in text.chars() {
-        let (glyph_metrics, bitmap) = font.rasterize(c, size);
-
-        // Calculate glyph position
-        let glyph_x = cursor_x + glyph_metrics.xmin as f32;
-        let glyph_y = baseline_y - glyph_metrics.height as f32 - glyph_metrics.ymin as f32;
+    let baseline_y = y as f32 + font.line_metrics(size).ascent;
+
+    let shaped_text = shape_text(font, text, size);
+    effects::draw_effects(
+        canvas,
+        &shaped_text.glyphs,
+        x as f32,
+        baseline_y,
+        clip_rect,
+        &style.effects,
+    );
+
+    for shaped in &shaped_text.glyphs {
+        let glyph = &shaped.glyph;
+        let (glyph_metrics, bitmap) = (&glyph.metrics, &glyph.bitmap);
+        let (glyph_x, glyph_y) = glyph_position(shaped, x as f32, baseline_y);
 
         // Draw the glyph bitmap
         for row in 0..glyph_metrics.height {
             for col in 0..glyph_metrics.width {
                 let alpha = bitmap[row * glyph_metrics.width + col];
                 if alpha > 0 {
-                    let px = glyph_x as i32 + col as i32;
-                    let py = glyph_y as i32 + row as i32;
+                    // Bitmap font glyphs keep their own colors
+                    let color = glyph.pixels.as_ref().map_or(style.color, |pixels| {
+                        let [r, g, b, _] = pixels.get_pixel(col as u32, row as u32).0;
+                        (r as u32) << 16 | (g as u32) << 8 | (b as u32)
+                    });
+                    let px = glyph_x + col as i32;
+                    let py = glyph_y + row as i32;
 
                     // Clip to rect if provided
                     if let Some(clip) = clip_rect {
@@ -166,27 +117,24 @@ pub fn draw_text_sized(
                         }
                     }
 
-                    // Clip to canvas and draw with alpha blending
-                    if px >= 0 && py >= 0 && (px as u32) < canvas.width() && (py as u32) < canvas.height() {
-                        if alpha == 255 {
-                            canvas.set_pixel(px as u32, py as u32, style.color);
-                        } else {
-                            // Simple alpha blend with black background
-                            let r = ((style.color >> 16) & 0xFF) as u32 * alpha as u32 / 255;
-                            let g = ((style.color >> 8) & 0xFF) as u32 * alpha as u32 / 255;
-                            let b = (style.color & 0xFF) as u32 * alpha as u32 / 255;
-                            let blended = (r << 16) | (g << 8) | b;
-                            canvas.set_pixel(px as u32, py as u32, blended);
-                        }
+                    // Blend the glyph coverage over whatever is already drawn
+                    if px >= 0 && py >= 0 {
+                        canvas.blend_pixel(px as u32, py as u32, color, alpha);
                     }
                 }
             }
         }
-
-        cursor_x += glyph_metrics.advance_width;
     }
 }
 
+/// Top-left corner of a shaped glyph's bitmap, for text starting at `x`.
+fn glyph_position(shaped: &ShapedGlyph, x: f32, baseline_y: f32) -> (i32, i32) {
+    let metrics = &shaped.glyph.metrics;
+    let glyph_x = x + shaped.x + metrics.xmin as f32;
+    let glyph_y = baseline_y - shaped.y - metrics.height as f32 - metrics.ymin as f32;
+    (glyph_x as i32, glyph_y as i32)
+}
+
 /// Draw a vertical caret (cursor) at the given position.
 pub fn draw_caret(
     canvas: &mut Canvas,
@@ -219,7 +167,8 @@ pub fn draw_caret(
 pub enum FontError {
     Io(std::io::Error),
     Parse(String),
-    AlreadyInitialized,
+    /// A bitmap font's glyph images could not be loaded.
+    Image(image::ImageError),
 }
 
 impl std::fmt::Display for FontError {
@@ -227,9 +176,65 @@ impl std::fmt::Display for FontError {
         match self {
             FontError::Io(e) => write!(f, "IO error: {}", e),
             FontError::Parse(e) => write!(f, "Font parse error: {}", e),
-            FontError::AlreadyInitialized => write!(f, "Font already initialized"),
+            FontError::Image(e) => write!(f, "Font image error: {}", e),
         }
     }
 }
 
 impl std::error::Error for FontError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_blends_against_background() {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+        let font = Font::load(&root.join("demo_app.crix/skin/font.ttf")).unwrap();
+
+        let mut buffer = vec![0xDDDDDD; 64 * 32];
+        let mut canvas = Canvas::new(&mut buffer, 64, 32);
+        let style = TextStyle::with_color(0xFFFFFF);
+        draw_text_sized(&mut canvas, &font, 2, 2, None, "Ag", style, 20.0);
+
+        // Edge pixels lie between the background and the text color, never darker
+        assert!(buffer.contains(&0xFFFFFF));
+        assert!(buffer.iter().all(|&p| p & 0xFF >= 0xDD));
+    }
+
+    #[test]
+    fn test_effects_draw_behind_the_text() {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+        let font = Font::load(&root.join("demo_app.crix/skin/font.ttf")).unwrap();
+        let effects = TextEffects {
+            shadow: Some(TextShadow {
+                offset: (3, 3),
+                color: 0x0000FF,
+                blur: 0,
+            }),
+            outline: Some(TextOutline {
+                width: 1,
+                color: 0xFF0000,
+            }),
+            glow: Some(TextGlow {
+                radius: 4,
+                color: 0x00FF00,
+            }),
+        };
+        let style = TextStyle::with_color(0xFFFFFF).with_effects(effects);
+
+        let mut buffer = vec![0; 96 * 48];
+        let mut canvas = Canvas::new(&mut buffer, 96, 48);
+        draw_text_sized(&mut canvas, &font, 8, 8, None, "Hi", style, 24.0);
+
+        // Every effect shows somewhere, and the text stays on top
+        assert!(buffer.contains(&0xFFFFFF));
+        assert!(buffer.contains(&0xFF0000));
+        assert!(buffer.contains(&0x0000FF));
+        assert!(
+            buffer
+                .iter()
+                .any(|&p| p & 0x00FF00 != 0 && p & 0xFF0000 == 0)
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! Turning a string into positioned glyphs: bidi reordering, font
+//! fallback and shaping (kerning, ligatures, marks, contextual forms).
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use unicode_bidi::BidiInfo;
+
+use super::font::Font;
+use super::glyph_cache::Glyph;
+use super::lru::Lru;
+
+/// A glyph placed on a line of shaped text.
+#[derive(Debug, Clone)]
+pub struct ShapedGlyph {
+    pub glyph: Arc<Glyph>,
+    /// Pen position relative to the start of the line.
+    pub x: f32,
+    /// Shift up from the baseline.
+    pub y: f32,
+    /// Byte offset of the first character the glyph belongs to.
+    pub cluster: usize,
+}
+
+/// A line of text shaped into glyphs in visual (left to right) order.
+#[derive(Debug, Clone, Default)]
+pub struct ShapedText {
+    pub glyphs: Vec<ShapedGlyph>,
+    pub width: f32,
+    /// Caret x before every character and after the last one.
+    carets: Vec<f32>,
+}
+
+impl ShapedText {
+    /// Caret x offsets, indexed by character position (`0..=char_count`).
+    ///
+    /// In right-to-left runs the caret before a character sits on its right
+    /// edge, so offsets only increase with the index in left-to-right text.
+    pub fn carets(&self) -> &[f32] {
+        &self.carets
+    }
+
+    /// Caret x offset at a character position, clamped to the end of the text.
+    pub fn caret_x(&self, index: usize) -> f32 {
+        let index = index.min(self.carets.len().saturating_sub(1));
+        self.carets.get(index).copied().unwrap_or(0.0)
+    }
+}
+
+/// Shaped strings kept per font before the least recently used are evicted.
+pub const DEFAULT_SHAPE_CAPACITY: usize = 512;
+
+/// An LRU-bounded cache of shaped strings for one font, so text that is
+/// drawn, measured and hit-tested over and over is only shaped once.
+/// Entries are keyed by font size (as bits, so any size works) and text.
+#[derive(Debug)]
+pub(super) struct ShapeCache {
+    entries: Lru<(u32, String), Arc<ShapedText>>,
+}
+
+impl Default for ShapeCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_SHAPE_CAPACITY)
+    }
+}
+
+impl ShapeCache {
+    pub(super) fn new(capacity: usize) -> Self {
+        Self {
+            entries: Lru::new(capacity),
+        }
+    }
+
+    /// Get `text` shaped at `size`, calling `shape` on a miss.
+    pub(super) fn get(
+        &mut self,
+        text: &str,
+        size: f32,
+        shape: impl FnOnce() -> ShapedText,
+    ) -> Arc<ShapedText> {
+        let key = (size.to_bits(), text.to_string());
+        Arc::clone(self.entries.get_or_insert_with(key, || Arc::new(shape())))
+    }
+}
+
+/// The characters a group of glyphs was shaped from, and where they ended up.
+struct Cluster {
+    /// Byte range in the text.
+    start: usize,
+    end: usize,
+    rtl: bool,
+    left: f32,
+    right: f32,
+}
+
+/// Shape a single line of text with `font` at `size`. Results are cached
+/// per font, so shaping the same string again is cheap.
+pub fn shape_text(font: &Font, text: &str, size: f32) -> Arc<ShapedText> {
+    font.shaped(text, size, || shape_uncached(font, text, size))
+}
+
+fn shape_uncached(font: &Font, text: &str, size: f32) -> ShapedText {
+    let mut glyphs = Vec::new();
+    let mut clusters = Vec::new();
+    let mut pen = 0.0f32;
+
+    let bidi = BidiInfo::new(text, None);
+    for paragraph in &bidi.paragraphs {
+        let (levels, runs) = bidi.visual_runs(paragraph, paragraph.range.clone());
+        for run in runs {
+            let rtl = levels[run.start].is_rtl();
+            let mut pieces = font.itemize(&text[run.clone()]);
+            // Within a right-to-left run, logically later pieces go further left
+            if rtl {
+                pieces.reverse();
+            }
+
+            for (face, range) in pieces {
+                let (start, end) = (run.start + range.start, run.start + range.end);
+                // Left and right edge of each cluster, keyed by its first byte
+                let mut extents: BTreeMap<usize, (f32, f32)> = BTreeMap::new();
+                for shaped in face.shape(&text[start..end], rtl, size) {
+                    let cluster = start + shaped.cluster;
+                    let (dx, dy) = shaped.offset;
+                    glyphs.push(ShapedGlyph {
+                        glyph: shaped.glyph,
+                        x: pen + dx,
+                        y: dy,
+                        cluster,
+                    });
+                    let extent = extents.entry(cluster).or_insert((pen, pen));
+                    extent.0 = extent.0.min(pen);
+                    extent.1 = extent.1.max(pen + shaped.advance);
+                    pen += shaped.advance;
+                }
+
+                let starts: Vec<usize> = extents.keys().copied().collect();
+                for (i, (&cluster, &(left, right))) in extents.iter().enumerate() {
+                    let cluster_end = starts.get(i + 1).copied().unwrap_or(end);
+                    clusters.push(Cluster {
+                        start: cluster,
+                        end: cluster_end,
+                        rtl,
+                        left,
+                        right,
+                    });
+                }
+            }
+        }
+    }
+
+    let carets = caret_offsets(text, &clusters);
+    ShapedText {
+        glyphs,
+        width: pen,
+        carets,
+    }
+}
+
+/// Place a caret before each character and after the last one. Characters
+/// sharing a cluster (ligatures, marks) split its width evenly.
+fn caret_offsets(text: &str, clusters: &[Cluster]) -> Vec<f32> {
+    let char_starts: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+    let mut carets: Vec<Option<f32>> = vec![None; char_starts.len() + 1];
+
+    for cluster in clusters {
+        let first = char_starts.partition_point(|&i| i < cluster.start);
+        let count = char_starts[first..].partition_point(|&i| i < cluster.end);
+        let step = (cluster.right - cluster.left) / count.max(1) as f32;
+
+        for k in 0..count {
+            let (leading, trailing) = if cluster.rtl {
+                (
+                    cluster.right - step * k as f32,
+                    cluster.right - step * (k + 1) as f32,
+                )
+            } else {
+                (
+                    cluster.left + step * k as f32,
+                    cluster.left + step * (k + 1) as f32,
+                )
+            };
+            carets[first + k] = Some(leading);
+            if first + k + 1 == char_starts.len() {
+                carets[first + k + 1] = Some(trailing);
+            }
+        }
+    }
+
+    // Characters that produced no glyphs share the previous caret
+    let mut previous = 0.0;
+    carets
+        .into_iter()
+        .map(|caret| {
+            previous = caret.unwrap_or(previous);
+            previous
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn demo_font() -> Font {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        Font::load(&root.join("demo_app.crix/skin/font.ttf")).unwrap()
+    }
+
+    #[test]
+    fn test_left_to_right_carets_follow_glyphs() {
+        let font = demo_font();
+        let shaped = shape_text(&font, "AVA", 16.0);
+
+        assert_eq!(shaped.carets().len(), 4);
+        assert!(shaped.carets().windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(shaped.caret_x(3), shaped.width);
+        assert_eq!(shaped.caret_x(99), shaped.width);
+        let clusters: Vec<_> = shaped.glyphs.iter().map(|g| g.cluster).collect();
+        assert_eq!(clusters, [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_right_to_left_runs_are_reordered() {
+        let font = demo_font();
+        // Two Hebrew letters after a Latin one: the Hebrew run is drawn
+        // right to left, so its first letter is rightmost
+        let text = "a\u{5D0}\u{5D1}";
+        let shaped = shape_text(&font, text, 16.0);
+
+        let order: Vec<_> = shaped.glyphs.iter().map(|g| g.cluster).collect();
+        assert_eq!(order, [0, 3, 1]);
+        let carets = shaped.carets();
+        assert!(
+            carets[1] > carets[2],
+            "caret before alef is right of the one before bet"
+        );
+        assert_eq!(shape_text(&Font::default(), text, 16.0).width, 0.0);
+    }
+
+    #[test]
+    fn test_shaped_text_is_cached_per_text_and_size() {
+        let font = demo_font();
+        let shaped = shape_text(&font, "cache", 16.0);
+
+        assert!(Arc::ptr_eq(
+            &shaped,
+            &shape_text(&font.clone(), "cache", 16.0)
+        ));
+        assert!(!Arc::ptr_eq(&shaped, &shape_text(&font, "cache", 18.0)));
+        assert!(!Arc::ptr_eq(&shaped, &shape_text(&font, "cached", 16.0)));
+
+        let mut cache = ShapeCache::new(2);
+        cache.get("a", 16.0, ShapedText::default);
+        cache.get("b", 16.0, ShapedText::default);
+        cache.get("a", 16.0, ShapedText::default);
+        // "b" is now the least recently used and makes room for "c"
+        cache.get("c", 12.0, ShapedText::default);
+        assert_eq!(cache.entries.len(), 2);
+        assert!(
+            cache
+                .entries
+                .contains_key(&(16.0f32.to_bits(), "a".to_string()))
+        );
+        assert!(
+            !cache
+                .entries
+                .contains_key(&(16.0f32.to_bits(), "b".to_string()))
+        );
+    }
+}
@@ -10,10 +10,14 @@ pub mod widgets;
 pub use bundle::{AppBundle, BundleError};
 pub use core::{
     Action, ActionDispatcher, ActionError, ActionHandler, App, AppRunner, KeyCode, Node, NodeId,
-    Rect, Services, Store, UiTree, Value, View, Widget, WidgetEvent, WidgetState,
+    Rect, Services, Store, UiScale, UiTree, Value, View, Widget, WidgetEvent, WidgetState,
+    WindowRequest,
 };
-pub use graphics::{Canvas, Image, init_font, FontError};
-pub use platform::{run, RunConfig};
+pub use graphics::{Canvas, Font, FontError, FontRegistry, Image, OffscreenRenderer, Paint, Sprite};
+pub use platform::{run, RunConfig, SkinApp};
 pub use scripting::{AppConfig, AppConfigError, LuaActionHandler, LuaError};
-pub use skin::{LoadedSkin, SkinBuilder, SkinError, SkinVScroll, SkinWindow, StaticText, TextAlign, TextInput, VerticalAlign};
+pub use skin::{
+    LoadedSkin, SkinBuilder, SkinError, SkinVScroll, SkinWindow, StaticText, TextAlign, TextInput,
+    TextWrap, VerticalAlign,
+};
 pub use widgets::{Button, Container, ImageWidget, VScrollContainer};
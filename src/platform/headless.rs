@@ -0,0 +1,202 @@
+//! Headless rendering: draw a bundle's UI without opening a window.
+//!
+//! The UI tree is built from the bundle's skin exactly as `crix run` does,
+//! optionally filled in from a store state, and drawn with an
+//! `OffscreenRenderer` into an image.
+
+use std::path::Path;
+
+use image::RgbaImage;
+
+use crate::bundle::{AppBundle, BundleError};
+use crate::core::{Store, UiTree, View};
+use crate::graphics::OffscreenRenderer;
+use crate::skin::widgets::Checkbox;
+use crate::skin::{SkinBuilder, SkinError, StaticText, TextInput};
+
+/// Errors that can occur while rendering headlessly.
+#[derive(Debug)]
+pub enum HeadlessError {
+    Bundle(BundleError),
+    Skin(SkinError),
+    Image(image::ImageError),
+    Io(std::io::Error),
+    /// The store state file is not a JSON object of strings, numbers and bools.
+    InvalidState(String),
+    /// A visual test script is malformed or refers to unknown parts.
+    InvalidScript(String),
+}
+
+impl std::fmt::Display for HeadlessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HeadlessError::Bundle(e) => write!(f, "Bundle error: {}", e),
+            HeadlessError::Skin(e) => write!(f, "Skin error: {}", e),
+            HeadlessError::Image(e) => write!(f, "Image error: {}", e),
+            HeadlessError::Io(e) => write!(f, "IO error: {}", e),
+            HeadlessError::InvalidState(msg) => write!(f, "Invalid state: {}", msg),
+            HeadlessError::InvalidScript(msg) => write!(f, "Invalid test script: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for HeadlessError {}
+
+impl From<BundleError> for HeadlessError {
+    fn from(e: BundleError) -> Self {
+        HeadlessError::Bundle(e)
+    }
+}
+
+impl From<SkinError> for HeadlessError {
+    fn from(e: SkinError) -> Self {
+        HeadlessError::Skin(e)
+    }
+}
+
+impl From<image::ImageError> for HeadlessError {
+    fn from(e: image::ImageError) -> Self {
+        HeadlessError::Image(e)
+    }
+}
+
+impl From<std::io::Error> for HeadlessError {
+    fn from(e: std::io::Error) -> Self {
+        HeadlessError::Io(e)
+    }
+}
+
+/// Build the UI tree for a bundle, with widgets filled in from `store`.
+pub fn build_tree(bundle: &AppBundle, store: Option<&Store>) -> Result<UiTree, HeadlessError> {
+    let skin = bundle.load_skin()?;
+    let (mut tree, _window) = SkinBuilder::build(&skin)?;
+    if let Some(store) = store {
+        apply_store(&mut tree, store);
+    }
+    Ok(tree)
+}
+
+/// Render a bundle's UI to an image.
+pub fn render_bundle(
+    bundle: &AppBundle,
+    store: Option<&Store>,
+) -> Result<RgbaImage, HeadlessError> {
+    let tree = build_tree(bundle, store)?;
+    Ok(render_view(&tree))
+}
+
+/// Render any view to an image at its preferred size.
+pub fn render_view(view: &dyn View) -> RgbaImage {
+    render_view_scaled(view, 1)
+}
+
+/// Render a view at its preferred size, with every UI pixel drawn as a
+/// `scale` x `scale` block as in a window at that scale.
+pub fn render_view_scaled(view: &dyn View, scale: u32) -> RgbaImage {
+    let (width, height) = view.size();
+    let mut renderer = OffscreenRenderer::new(width, height).with_scale(scale);
+    renderer.render(view);
+    renderer.to_image()
+}
+
+/// Render the bundle at `bundle_path` and save it as a PNG.
+pub fn screenshot(
+    bundle_path: &Path,
+    store: Option<&Store>,
+    output: &Path,
+) -> Result<(), HeadlessError> {
+    let bundle = AppBundle::load(bundle_path)?;
+    let image = render_bundle(&bundle, store)?;
+    image.save_with_format(output, image::ImageFormat::Png)?;
+    Ok(())
+}
+
+/// Push store values into the widgets bound to them: static texts show the
+/// value, text inputs contain it and checkboxes are checked from it.
+pub fn apply_store(tree: &mut UiTree, store: &Store) {
+    let node_ids: Vec<_> = tree.iter_node_ids().collect();
+
+    for id in node_ids {
+        let Some(node) = tree.get_mut(id) else {
+            continue;
+        };
+        let widget = node.widget_mut().as_any_mut();
+
+        if let Some(text) = widget.downcast_mut::<StaticText>() {
+            if let Some(binding) = text.binding()
+                && store.contains(binding)
+            {
+                let value = store.get_string(binding);
+                text.set_content(value);
+            }
+        } else if let Some(input) = widget.downcast_mut::<TextInput>() {
+            if let Some(binding) = input.binding()
+                && store.contains(binding)
+            {
+                let value = store.get_string(binding);
+                input.set_text(value);
+                input.clear_dirty();
+            }
+        } else if let Some(checkbox) = widget.downcast_mut::<Checkbox>()
+            && let Some(binding) = checkbox.binding()
+            && store.contains(binding)
+        {
+            let checked = store.get_bool(binding);
+            checkbox.set_checked(checked);
+            checkbox.clear_dirty();
+        }
+    }
+}
+
+/// Load a store state from a JSON object such as `{"gallons": "12", "imperial": true}`.
+pub fn load_state(path: &Path) -> Result<Store, HeadlessError> {
+    let content = std::fs::read_to_string(path)?;
+    let json: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| HeadlessError::InvalidState(e.to_string()))?;
+    let serde_json::Value::Object(entries) = json else {
+        return Err(HeadlessError::InvalidState(
+            "expected a JSON object".to_string(),
+        ));
+    };
+    state_from_json(entries)
+}
+
+/// Build a store from the entries of a JSON state object.
+pub fn state_from_json(
+    entries: serde_json::Map<String, serde_json::Value>,
+) -> Result<Store, HeadlessError> {
+    let mut store = Store::new();
+    for (key, value) in entries {
+        match value {
+            serde_json::Value::String(s) => store.set(key, s),
+            serde_json::Value::Bool(b) => store.set(key, b),
+            serde_json::Value::Number(n) => store.set(key, n.as_f64().unwrap_or_default()),
+            other => {
+                return Err(HeadlessError::InvalidState(format!(
+                    "'{}' must be a string, number or bool, got {}",
+                    key, other
+                )));
+            }
+        }
+    }
+    Ok(store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_demo_bundle_with_state() {
+        let bundle_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("demo_app.crix");
+        let bundle = AppBundle::load(&bundle_path).unwrap();
+        let window = bundle.load_skin().unwrap().window().clone();
+
+        let mut store = Store::new();
+        store.set("outputs.e85_to_add_liters", "42");
+        let image = render_bundle(&bundle, Some(&store)).unwrap();
+
+        assert_eq!(image.dimensions(), (window.width, window.height));
+        assert!(image.pixels().any(|p| p.0 != [0, 0, 0, 255]));
+    }
+}
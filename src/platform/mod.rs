@@ -1,3 +1,7 @@
+pub mod headless;
+mod skin_app;
+pub mod visual;
 mod window;
 
+pub use skin_app::SkinApp;
 pub use window::{run, RunConfig};
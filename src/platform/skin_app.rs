@@ -0,0 +1,581 @@
+//! The app `crix run` runs: a bundle's skin driven by window events.
+//!
+//! Pointer and keyboard input reach the widgets under the pointer or in
+//! focus, bound inputs are written to the store, and button and checkbox
+//! actions run through the bundle's scripts. Visual tests drive the same
+//! app with simulated events.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::Instant;
+
+use serde::Deserialize;
+use winit::event::{ElementState, MouseScrollDelta, WindowEvent};
+use winit::keyboard::{Key, NamedKey};
+
+use crate::bundle::AppBundle;
+use crate::core::{
+    Action, ActionDispatcher, App, HitRegion, KeyCode, NodeId, Rect, Services, Store, UiTree, View,
+    WidgetEvent, WindowRequest,
+};
+use crate::platform::headless;
+use crate::scripting::LuaActionHandler;
+use crate::skin::widgets::{Checkbox, DragRegion, FilePicker, SkinButton};
+use crate::skin::{SkinBuilder, SkinError, SkinWindow, StaticText, TextInput};
+
+/// Lightweight app metadata parsed from app.toml for display.
+#[derive(Debug, Deserialize)]
+struct AppTomlMeta {
+    app: AppMetaSection,
+}
+
+#[derive(Debug, Deserialize)]
+struct AppMetaSection {
+    name: String,
+    #[serde(default)]
+    version: String,
+    #[serde(default)]
+    author: String,
+    #[serde(default)]
+    description: String,
+}
+
+/// Load app metadata from a .crix bundle's app.toml.
+fn load_app_metadata(bundle_path: &PathBuf) -> Option<AppMetaSection> {
+    let toml_path = bundle_path.join("app.toml");
+    let content = fs::read_to_string(&toml_path).ok()?;
+    let parsed: AppTomlMeta = toml::from_str(&content).ok()?;
+    Some(parsed.app)
+}
+
+/// A bundle's skin running as an app, with its store and action scripts.
+pub struct SkinApp {
+    tree: UiTree,
+    title: String,
+    window: SkinWindow,
+    /// Shape of an irregular window; points outside it don't reach the UI.
+    shape: Option<HitRegion>,
+    window_requests: Vec<WindowRequest>,
+    store: Store,
+    dispatcher: ActionDispatcher,
+    services: Services,
+}
+
+impl SkinApp {
+    /// Build the bundle's UI and load its action scripts.
+    pub fn new(bundle: AppBundle) -> Result<Self, SkinError> {
+        // Load skin from bundle
+        let skin = bundle.load_skin()?;
+        let title = format!("{} - {}", bundle.meta.name, skin.name());
+
+        // Build UI tree from skin
+        let (tree, window) = SkinBuilder::build(&skin)?;
+        let shape = SkinBuilder::window_shape(&skin)?;
+
+        // Set up the store and dispatcher
+        let store = Store::new();
+        let mut dispatcher = ActionDispatcher::new();
+
+        // Build action scripts HashMap for LuaActionHandler
+        let mut action_scripts = HashMap::new();
+        for action_name in bundle.action_names() {
+            if let Some(path) = bundle.get_script(action_name) {
+                action_scripts.insert(action_name.clone(), path.to_path_buf());
+            }
+        }
+        let lua_handler = LuaActionHandler::from_scripts(action_scripts);
+        dispatcher.add_handler(lua_handler);
+
+        let services = Services::new();
+
+        Ok(Self {
+            tree,
+            title,
+            window,
+            shape,
+            window_requests: Vec::new(),
+            store,
+            dispatcher,
+            services,
+        })
+    }
+
+    /// Start from `store`, showing its values in the widgets bound to them.
+    pub fn with_state(mut self, store: Store) -> Self {
+        headless::apply_store(&mut self.tree, &store);
+        self.store = store;
+        self
+    }
+
+    /// Window title: the app's name and the skin's.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Window settings from the skin, with the current size.
+    pub fn window(&self) -> &SkinWindow {
+        &self.window
+    }
+
+    /// Shape of an irregular window, if the skin has one.
+    pub fn shape(&self) -> Option<&HitRegion> {
+        self.shape.as_ref()
+    }
+
+    pub fn tree(&self) -> &UiTree {
+        &self.tree
+    }
+
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    /// Sync text inputs to store (write dirty values).
+    fn sync_inputs_to_store(&mut self) {
+        let node_ids = self.tree.find_widgets::<TextInput>(|t| t.is_dirty());
+
+        for id in node_ids {
+            if let Some(node) = self.tree.get_mut(id) {
+                if let Some(text_input) = node.widget_mut().as_any_mut().downcast_mut::<TextInput>()
+                {
+                    if let Some(binding) = text_input.binding() {
+                        let text = text_input.text().to_string();
+                        self.store.set(binding.to_string(), text);
+                    }
+                    text_input.clear_dirty();
+                }
+            }
+        }
+    }
+
+    /// Sync checkboxes to store (write dirty values).
+    fn sync_checkboxes_to_store(&mut self) {
+        let node_ids = self.tree.find_widgets::<Checkbox>(|c| c.is_dirty());
+
+        for id in node_ids {
+            if let Some(node) = self.tree.get_mut(id) {
+                if let Some(checkbox) = node.widget_mut().as_any_mut().downcast_mut::<Checkbox>() {
+                    if let Some(binding) = checkbox.binding() {
+                        self.store.set(binding.to_string(), checkbox.is_checked());
+                    }
+                    checkbox.clear_dirty();
+                }
+            }
+        }
+    }
+
+    /// Sync store values to static text widgets (update displays).
+    fn sync_store_to_outputs(&mut self) {
+        // Only touch texts whose value changed, so the rest aren't repainted
+        let store = &self.store;
+        let node_ids = self.tree.find_widgets::<StaticText>(|t| {
+            t.binding().is_some_and(|binding| {
+                let value = store.get_string(binding);
+                !value.is_empty() && value != t.content()
+            })
+        });
+
+        for id in node_ids {
+            if let Some(node) = self.tree.get_mut(id) {
+                if let Some(static_text) =
+                    node.widget_mut().as_any_mut().downcast_mut::<StaticText>()
+                {
+                    if let Some(binding) = static_text.binding() {
+                        let value = self.store.get_string(binding);
+                        static_text.set_content(value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Dispatch an action by name.
+    fn dispatch_action(&mut self, name: &str) {
+        let action = Action::new(name);
+        if let Err(e) = self
+            .dispatcher
+            .dispatch(&action, &mut self.store, &self.services)
+        {
+            eprintln!("Action error: {}", e);
+        }
+    }
+
+    /// Get the action for a clicked widget (if it's a button).
+    fn get_button_action(&self, node_id: NodeId) -> Option<String> {
+        if let Some(node) = self.tree.get(node_id) {
+            // Try to get the action from a SkinButton
+            if let Some(button) = node.widget().as_any().downcast_ref::<SkinButton>() {
+                return button.action().map(|s| s.to_string());
+            }
+        }
+        None
+    }
+
+    /// Whether a window point is inside the window's shape.
+    fn in_shape(&self, x: i32, y: i32) -> bool {
+        let window = Rect::new(0, 0, self.window.width, self.window.height);
+        self.shape
+            .as_ref()
+            .is_none_or(|shape| shape.contains(&window, x, y))
+    }
+
+    /// Whether the node is a drag region, which moves the window instead of
+    /// taking clicks.
+    fn is_drag_region(&self, node_id: NodeId) -> bool {
+        self.tree
+            .get(node_id)
+            .is_some_and(|node| node.widget().as_any().is::<DragRegion>())
+    }
+
+    /// Check for FilePicker pending actions and handle them.
+    fn handle_file_picker_actions(&mut self) {
+        // Collect pending actions first to avoid borrow conflicts
+        let mut actions_to_process: Vec<(String, PathBuf)> = Vec::new();
+
+        let node_ids = self
+            .tree
+            .find_widgets::<FilePicker>(|p| p.has_pending_action());
+        for id in node_ids {
+            if let Some(node) = self.tree.get_mut(id) {
+                if let Some(picker) = node.widget_mut().as_any_mut().downcast_mut::<FilePicker>() {
+                    if let Some(action) = picker.on_select_action() {
+                        if let Some(path) = picker.selected_file().cloned() {
+                            actions_to_process.push((action.to_string(), path));
+                        }
+                    }
+                    picker.clear_pending_action();
+                }
+            }
+        }
+
+        // Now process the collected actions
+        for (action, path) in actions_to_process {
+            match action.as_str() {
+                "launch_child_app" => {
+                    launch_child_app(&path);
+                }
+                "load_app_info" => {
+                    self.load_app_info_to_store(&path);
+                }
+                _ => {
+                    // Could dispatch to Lua handler here
+                }
+            }
+        }
+    }
+
+    /// Load app metadata from a .crix bundle and populate the store.
+    fn load_app_info_to_store(&mut self, path: &PathBuf) {
+        // Store the selected path
+        self.store.set(
+            "selected_app_path".to_string(),
+            path.to_string_lossy().to_string(),
+        );
+
+        // Try to load and parse app.toml
+        if let Some(meta) = load_app_metadata(path) {
+            self.store.set("app_name".to_string(), meta.name);
+            self.store.set("app_version".to_string(), meta.version);
+            self.store.set("app_author".to_string(), meta.author);
+            self.store
+                .set("app_description".to_string(), meta.description);
+        } else {
+            // Clear metadata if parsing failed
+            let dir_name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            self.store.set("app_name".to_string(), dir_name);
+            self.store.set("app_version".to_string(), "".to_string());
+            self.store.set("app_author".to_string(), "".to_string());
+            self.store.set(
+                "app_description".to_string(),
+                "(Could not read app.toml)".to_string(),
+            );
+        }
+
+        // Sync the store values to StaticText widgets
+        self.sync_store_to_outputs();
+    }
+
+    /// Handle the launch_selected_app action (triggered by Run button).
+    fn handle_launch_selected_app(&mut self) {
+        let path_str = self.store.get_string("selected_app_path");
+        if !path_str.is_empty() {
+            let path = PathBuf::from(path_str);
+            if path.exists() && path.is_dir() {
+                launch_child_app(&path);
+            }
+        }
+    }
+
+    /// Handle checkbox actions for the currently pressed widget.
+    fn handle_checkbox_actions(&mut self) {
+        if let Some(pressed_id) = self.tree.pressed() {
+            // Get the action from the checkbox if it is one
+            let action = {
+                if let Some(node) = self.tree.get(pressed_id) {
+                    if let Some(checkbox) = node.widget().as_any().downcast_ref::<Checkbox>() {
+                        checkbox.action().map(|s| s.to_string())
+                    } else {
+                        None
+                    }
+                } else {
+                    None
+                }
+            };
+
+            // Dispatch the action if present
+            if let Some(action_name) = action {
+                // Sync inputs first (in case checkbox state affects calculations)
+                self.sync_inputs_to_store();
+
+                // Dispatch the action
+                self.dispatch_action(&action_name);
+
+                // Sync outputs after action
+                self.sync_store_to_outputs();
+            }
+        }
+    }
+
+    /// Handle a key press: named editing keys and printable characters go
+    /// to the focused widget. Returns whether anything handled it.
+    pub fn key_pressed(&mut self, key: &Key) -> bool {
+        // Route keyboard events to focused widget
+        if let Some(focused_id) = self.tree.focused() {
+            let widget_event = match key {
+                Key::Named(NamedKey::Backspace) => Some(WidgetEvent::KeyDown {
+                    key: KeyCode::Backspace,
+                }),
+                Key::Named(NamedKey::Delete) => Some(WidgetEvent::KeyDown {
+                    key: KeyCode::Delete,
+                }),
+                Key::Named(NamedKey::ArrowLeft) => {
+                    Some(WidgetEvent::KeyDown { key: KeyCode::Left })
+                }
+                Key::Named(NamedKey::ArrowRight) => Some(WidgetEvent::KeyDown {
+                    key: KeyCode::Right,
+                }),
+                Key::Named(NamedKey::Home) => Some(WidgetEvent::KeyDown { key: KeyCode::Home }),
+                Key::Named(NamedKey::End) => Some(WidgetEvent::KeyDown { key: KeyCode::End }),
+                Key::Named(NamedKey::Enter) => Some(WidgetEvent::KeyDown {
+                    key: KeyCode::Enter,
+                }),
+                Key::Character(s) => {
+                    // Only handle single ASCII characters
+                    if s.len() == 1 {
+                        let c = s.chars().next().unwrap();
+                        if c as u32 >= 32 && c as u32 <= 126 {
+                            Some(WidgetEvent::CharInput { c })
+                        } else {
+                            None
+                        }
+                    } else {
+                        None
+                    }
+                }
+                Key::Named(NamedKey::Space) => Some(WidgetEvent::CharInput { c: ' ' }),
+                _ => None,
+            };
+
+            if let Some(widget_event) = widget_event {
+                if let Some(node) = self.tree.get_mut(focused_id) {
+                    node.widget_mut().on_event(&widget_event);
+                }
+                // Sync after input
+                self.sync_inputs_to_store();
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Launch a child crix app in a new process.
+fn launch_child_app(path: &PathBuf) {
+    println!("Launching app: {}", path.display());
+
+    // Get the path to the current executable
+    let exe = std::env::current_exe().expect("Failed to get current executable path");
+
+    // Spawn a new process to run the child app
+    match Command::new(&exe).arg("run").arg(path).spawn() {
+        Ok(child) => {
+            println!("Launched child process with PID: {}", child.id());
+        }
+        Err(e) => {
+            eprintln!("Failed to launch app: {}", e);
+        }
+    }
+}
+
+impl App for SkinApp {
+    fn view(&self) -> &dyn View {
+        &self.tree
+    }
+
+    fn next_frame(&self) -> Option<Instant> {
+        self.tree.next_frame()
+    }
+
+    fn take_damage(&mut self) -> Option<Vec<Rect>> {
+        Some(self.tree.take_damage())
+    }
+
+    fn take_window_requests(&mut self) -> Vec<WindowRequest> {
+        std::mem::take(&mut self.window_requests)
+    }
+
+    fn on_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::Resized(size) => {
+                // Minimized windows report a zero size; keep the last layout
+                let size = (size.width, size.height);
+                if size.0 == 0 || size.1 == 0 || size == (self.window.width, self.window.height) {
+                    return false;
+                }
+                (self.window.width, self.window.height) = size;
+                if let Some(root) = self.tree.root() {
+                    self.tree.set_bounds(root, Rect::new(0, 0, size.0, size.1));
+                    self.tree.apply_layout(root);
+                }
+                true
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let x = position.x as i32;
+                let y = position.y as i32;
+                let hit = if self.in_shape(x, y) {
+                    self.tree.hit_test(x, y)
+                } else {
+                    None
+                };
+                self.tree.set_hovered(hit);
+
+                // Send MouseMove event to hovered widget for position tracking,
+                // repainting it only if the widget changed
+                if let Some(hovered_id) = hit
+                    && let Some(node) = self.tree.get_mut_untracked(hovered_id)
+                    && node.widget_mut().on_event(&WidgetEvent::MouseMove { x, y })
+                {
+                    self.tree.invalidate(hovered_id);
+                }
+                true
+            }
+            WindowEvent::MouseInput { state, .. } => {
+                match state {
+                    ElementState::Pressed => {
+                        // Drag regions hand the pointer to the OS to move the window
+                        if let Some(hovered) = self.tree.hovered()
+                            && self.is_drag_region(hovered)
+                        {
+                            self.window_requests.push(WindowRequest::Drag);
+                            return true;
+                        }
+
+                        // Set pressed state
+                        if let Some(hovered) = self.tree.hovered() {
+                            self.tree.set_pressed(Some(hovered));
+
+                            // Focus the clicked widget (for text inputs)
+                            let old_focused = self.tree.focused();
+                            if old_focused != Some(hovered) {
+                                // Notify old focused widget of focus loss
+                                if let Some(old_id) = old_focused {
+                                    if let Some(node) = self.tree.get_mut(old_id) {
+                                        node.widget_mut().on_event(&WidgetEvent::FocusLost);
+                                    }
+                                }
+                                // Set new focus
+                                self.tree.set_focused(Some(hovered));
+                                // Notify new widget of focus gain
+                                if let Some(node) = self.tree.get_mut(hovered) {
+                                    node.widget_mut().on_event(&WidgetEvent::FocusGained);
+                                }
+                            }
+                        } else {
+                            // Clicked outside any widget, clear focus
+                            if let Some(old_id) = self.tree.focused() {
+                                if let Some(node) = self.tree.get_mut(old_id) {
+                                    node.widget_mut().on_event(&WidgetEvent::FocusLost);
+                                }
+                            }
+                            self.tree.set_focused(None);
+                        }
+                    }
+                    ElementState::Released => {
+                        if let Some(pressed_id) = self.tree.pressed() {
+                            // Check if we're still hovering the pressed widget
+                            if self.tree.hovered() == Some(pressed_id) {
+                                // Get action before mutably borrowing tree
+                                let action = self.get_button_action(pressed_id);
+
+                                // Send click event to widget
+                                if let Some(node) = self.tree.get_mut(pressed_id) {
+                                    node.widget_mut().on_event(&WidgetEvent::Click);
+                                }
+
+                                // Sync checkboxes after click (they toggle on click)
+                                self.sync_checkboxes_to_store();
+
+                                // Handle checkbox actions (dispatch if checkbox has an action)
+                                self.handle_checkbox_actions();
+
+                                // Handle file picker actions (must be after click event)
+                                self.handle_file_picker_actions();
+
+                                // Dispatch action if this was a button
+                                if let Some(action_name) = action {
+                                    // Sync inputs first
+                                    self.sync_inputs_to_store();
+
+                                    // Handle built-in actions
+                                    match action_name.as_str() {
+                                        "launch_selected_app" => self.handle_launch_selected_app(),
+                                        "minimize" => {
+                                            self.window_requests.push(WindowRequest::Minimize)
+                                        }
+                                        "close" => self.window_requests.push(WindowRequest::Close),
+                                        // Dispatch the action to Lua handler
+                                        _ => self.dispatch_action(&action_name),
+                                    }
+
+                                    // Sync outputs after action
+                                    self.sync_store_to_outputs();
+                                }
+                            }
+                        }
+                        self.tree.set_pressed(None);
+                    }
+                }
+                true
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                // Convert delta to pixels (rough approximation)
+                let delta_y = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y * 20.0,
+                    MouseScrollDelta::PixelDelta(pos) => pos.y as f32,
+                };
+
+                // Route to hovered widget
+                if let Some(hovered_id) = self.tree.hovered() {
+                    if let Some(node) = self.tree.get_mut(hovered_id) {
+                        if node
+                            .widget_mut()
+                            .on_event(&WidgetEvent::MouseWheel { delta_y })
+                        {
+                            return true;
+                        }
+                    }
+                }
+                false
+            }
+            WindowEvent::KeyboardInput { event, .. } => {
+                event.state.is_pressed() && self.key_pressed(&event.logical_key)
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,502 @@
+//! Golden-image visual regression tests for bundles.
+//!
+//! A test script (JSON) lists steps that hover, click and type into skin
+//! parts by id, or point at a position in UI pixels. The bundle runs as it
+//! does under `crix run`: pointer moves and clicks are sent to the app as
+//! window events in screen pixels, so they reach whatever part the hit test
+//! finds there, scaled by `scale`. After each step the UI is rendered
+//! headlessly and compared with a reference PNG named after the step:
+//!
+//! ```json
+//! {
+//!   "tolerance": 2,
+//!   "scale": 2,
+//!   "steps": [
+//!     { "name": "initial" },
+//!     { "name": "hover_calculate", "hover": "calculate_button" },
+//!     { "name": "typed", "click": "current_fuel.input", "type": "40" },
+//!     { "name": "corner", "click": [4, 4] }
+//!   ]
+//! }
+//! ```
+//!
+//! Steps that don't match their reference get `<name>.actual.png` and
+//! `<name>.diff.png` written to the output directory
+//! (`target/visual-failures/<bundle>/` unless given). Run
+//! `crix test-visual <bundle> --update` to (re)create the references.
+
+use std::path::{Path, PathBuf};
+
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use image::{DynamicImage, Rgba, RgbaImage};
+use serde::Deserialize;
+use winit::dpi::PhysicalPosition;
+use winit::event::{DeviceId, ElementState, MouseButton, WindowEvent};
+use winit::keyboard::{Key, NamedKey};
+
+use super::headless::{self, HeadlessError};
+use super::window::to_ui_space;
+use super::SkinApp;
+use crate::bundle::AppBundle;
+use crate::core::{App, NodeId, Store};
+
+/// A visual test script.
+#[derive(Debug, Deserialize)]
+pub struct VisualScript {
+    /// Largest per-channel difference still considered equal.
+    #[serde(default)]
+    pub tolerance: u8,
+    /// Number of differing pixels allowed before a step fails.
+    #[serde(default)]
+    pub max_diff_pixels: u32,
+    /// Screen pixels per UI pixel, as set by `crix run --scale`.
+    #[serde(default = "default_scale")]
+    pub scale: u32,
+    /// Initial store values, as for `crix screenshot --state`.
+    #[serde(default)]
+    pub state: serde_json::Map<String, serde_json::Value>,
+    pub steps: Vec<VisualStep>,
+}
+
+fn default_scale() -> u32 {
+    1
+}
+
+/// One step of a visual test. Interactions run in the order hover, click,
+/// type, key; a step without any just renders the current UI.
+#[derive(Debug, Deserialize)]
+pub struct VisualStep {
+    /// Reference image name (`<name>.png`).
+    pub name: String,
+    /// Move the pointer here.
+    #[serde(default)]
+    pub hover: Option<Target>,
+    /// Move the pointer here, then press and release it.
+    #[serde(default)]
+    pub click: Option<Target>,
+    /// Type text into the focused part.
+    #[serde(default, rename = "type")]
+    pub text: Option<String>,
+    /// Press a named key: backspace, delete, left, right, home, end or enter.
+    #[serde(default)]
+    pub key: Option<String>,
+}
+
+/// Where a step puts the pointer.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Target {
+    /// The center of a part, which must be the part found there.
+    Part(String),
+    /// A point in UI pixels, whatever is there.
+    Point(i32, i32),
+}
+
+impl VisualScript {
+    /// Load a script from a JSON file.
+    pub fn load(path: &Path) -> Result<Self, HeadlessError> {
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|e| HeadlessError::InvalidScript(e.to_string()))
+    }
+}
+
+/// A running bundle driven by simulated input instead of a window.
+///
+/// The bundle runs as a `SkinApp`, the app `crix run` opens, and gets the
+/// window events a window would send it.
+pub struct VisualSession {
+    app: SkinApp,
+    scale: u32,
+}
+
+impl VisualSession {
+    /// Run the bundle's app from `store`, as if in a window at `scale`.
+    pub fn new(bundle: AppBundle, store: Store, scale: u32) -> Result<Self, HeadlessError> {
+        let app = SkinApp::new(bundle)?.with_state(store);
+        Ok(Self {
+            app,
+            scale: scale.max(1),
+        })
+    }
+
+    pub fn app(&self) -> &SkinApp {
+        &self.app
+    }
+
+    /// Run a step's interactions.
+    pub fn apply(&mut self, step: &VisualStep) -> Result<(), HeadlessError> {
+        if let Some(target) = &step.hover {
+            self.hover(target)?;
+        }
+        if let Some(target) = &step.click {
+            self.click(target)?;
+        }
+        if let Some(text) = &step.text {
+            self.type_text(text);
+        }
+        if let Some(key) = &step.key {
+            self.key(key)?;
+        }
+        Ok(())
+    }
+
+    /// Move the pointer to a target.
+    pub fn hover(&mut self, target: &Target) -> Result<(), HeadlessError> {
+        let (x, y, part) = match target {
+            Target::Part(name) => {
+                let id = self.part(name)?;
+                let bounds = self
+                    .app
+                    .tree()
+                    .get(id)
+                    .map(|n| n.bounds)
+                    .unwrap_or_default();
+                let x = bounds.x + bounds.width as i32 / 2;
+                let y = bounds.y + bounds.height as i32 / 2;
+                (x, y, Some((name, id)))
+            }
+            Target::Point(x, y) => (*x, *y, None),
+        };
+
+        // The middle of the UI pixel, in screen pixels
+        let scale = self.scale as i32;
+        let (screen_x, screen_y) = (x * scale + scale / 2, y * scale + scale / 2);
+        self.send(WindowEvent::CursorMoved {
+            device_id: DeviceId::dummy(),
+            position: PhysicalPosition::new(screen_x as f64, screen_y as f64),
+        });
+
+        if let Some((name, id)) = part
+            && self.app.tree().hovered() != Some(id)
+        {
+            return Err(HeadlessError::InvalidScript(format!(
+                "part '{}' isn't what the pointer is over at its center ({}, {})",
+                name, x, y
+            )));
+        }
+        Ok(())
+    }
+
+    /// Click a target: move the pointer there, press and release.
+    pub fn click(&mut self, target: &Target) -> Result<(), HeadlessError> {
+        self.hover(target)?;
+        for state in [ElementState::Pressed, ElementState::Released] {
+            self.send(WindowEvent::MouseInput {
+                device_id: DeviceId::dummy(),
+                state,
+                button: MouseButton::Left,
+            });
+        }
+        Ok(())
+    }
+
+    /// Type text into the focused part, one key press per character.
+    pub fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            // Windows report the space bar as a named key
+            let key = match c {
+                ' ' => Key::Named(NamedKey::Space),
+                c => Key::Character(c.to_string().into()),
+            };
+            self.app.key_pressed(&key);
+        }
+    }
+
+    /// Press a named key in the focused part.
+    pub fn key(&mut self, name: &str) -> Result<(), HeadlessError> {
+        let key = match name.to_ascii_lowercase().as_str() {
+            "backspace" => NamedKey::Backspace,
+            "delete" => NamedKey::Delete,
+            "left" => NamedKey::ArrowLeft,
+            "right" => NamedKey::ArrowRight,
+            "home" => NamedKey::Home,
+            "end" => NamedKey::End,
+            "enter" => NamedKey::Enter,
+            _ => {
+                return Err(HeadlessError::InvalidScript(format!(
+                    "unknown key '{}'",
+                    name
+                )));
+            }
+        };
+        self.app.key_pressed(&Key::Named(key));
+        Ok(())
+    }
+
+    /// Render the UI at the window's size and scale.
+    pub fn render(&self) -> RgbaImage {
+        headless::render_view_scaled(self.app.view(), self.scale)
+    }
+
+    fn part(&self, part: &str) -> Result<NodeId, HeadlessError> {
+        self.app
+            .tree()
+            .find_by_name(part)
+            .ok_or_else(|| HeadlessError::InvalidScript(format!("unknown part '{}'", part)))
+    }
+
+    /// Hand a window event in screen pixels to the app, as the window does.
+    fn send(&mut self, event: WindowEvent) {
+        self.app.on_event(&to_ui_space(event, self.scale));
+        // Every render is a full one, and there is no window to move or close
+        self.app.take_damage();
+        self.app.take_window_requests();
+    }
+}
+
+/// The result of comparing a rendered image with its reference.
+#[derive(Debug)]
+pub struct ImageDiff {
+    /// Number of pixels differing by more than the tolerance.
+    pub diff_pixels: u32,
+    /// The reference dimmed, with differing pixels marked in red.
+    pub image: RgbaImage,
+}
+
+/// Compare two images pixel by pixel. Returns `None` if their sizes differ.
+pub fn compare_images(
+    actual: &RgbaImage,
+    reference: &RgbaImage,
+    tolerance: u8,
+) -> Option<ImageDiff> {
+    if actual.dimensions() != reference.dimensions() {
+        return None;
+    }
+
+    let mut diff_pixels = 0;
+    let mut image = RgbaImage::new(actual.width(), actual.height());
+    for ((a, r), out) in actual
+        .pixels()
+        .zip(reference.pixels())
+        .zip(image.pixels_mut())
+    {
+        let differs =
+            a.0.iter()
+                .zip(r.0.iter())
+                .any(|(&a, &r)| a.abs_diff(r) > tolerance);
+        *out = if differs {
+            diff_pixels += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            let luma = (r[0] as u32 * 3 + r[1] as u32 * 6 + r[2] as u32) / 10;
+            let dim = (luma / 3) as u8;
+            Rgba([dim, dim, dim, 255])
+        };
+    }
+    Some(ImageDiff { diff_pixels, image })
+}
+
+/// How a single step turned out.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepOutcome {
+    Passed,
+    /// The reference was (re)written from the rendered image.
+    Updated,
+    /// No reference image exists for the step.
+    MissingReference,
+    SizeMismatch {
+        expected: (u32, u32),
+        actual: (u32, u32),
+    },
+    Failed {
+        diff_pixels: u32,
+    },
+}
+
+/// The result of one step.
+#[derive(Debug, Clone)]
+pub struct StepResult {
+    pub name: String,
+    pub outcome: StepOutcome,
+}
+
+impl StepResult {
+    pub fn passed(&self) -> bool {
+        matches!(self.outcome, StepOutcome::Passed | StepOutcome::Updated)
+    }
+}
+
+/// A visual test run against a bundle.
+pub struct VisualTest {
+    bundle: PathBuf,
+    script: PathBuf,
+    references: PathBuf,
+    output: PathBuf,
+    update: bool,
+}
+
+impl VisualTest {
+    /// Test the bundle at `bundle` using `tests/visual.json` and the
+    /// references in `tests/visual/`. Failures go to
+    /// `target/visual-failures/<bundle name>/`, outside the bundle.
+    pub fn new(bundle: impl Into<PathBuf>) -> Self {
+        let bundle = bundle.into();
+        let references = bundle.join("tests").join("visual");
+        let name = bundle.file_name().map(PathBuf::from).unwrap_or_default();
+        Self {
+            script: bundle.join("tests").join("visual.json"),
+            output: Path::new("target").join("visual-failures").join(name),
+            references,
+            bundle,
+            update: false,
+        }
+    }
+
+    /// Use a different script file.
+    pub fn with_script(mut self, script: impl Into<PathBuf>) -> Self {
+        self.script = script.into();
+        self
+    }
+
+    /// Use a different reference image directory.
+    pub fn with_references(mut self, references: impl Into<PathBuf>) -> Self {
+        self.references = references.into();
+        self
+    }
+
+    /// Write actual and diff images for failing steps here.
+    pub fn with_output(mut self, output: impl Into<PathBuf>) -> Self {
+        self.output = output.into();
+        self
+    }
+
+    /// Overwrite the reference images with the rendered ones.
+    pub fn with_update(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    /// Replay the script and compare every step.
+    pub fn run(&self) -> Result<Vec<StepResult>, HeadlessError> {
+        let script = VisualScript::load(&self.script)?;
+        let bundle = AppBundle::load(&self.bundle)?;
+        let store = headless::state_from_json(script.state.clone())?;
+        let mut session = VisualSession::new(bundle, store, script.scale)?;
+
+        let mut results = Vec::with_capacity(script.steps.len());
+        for step in &script.steps {
+            session.apply(step)?;
+            let actual = session.render();
+            let outcome = self.check(&step.name, &actual, &script)?;
+            results.push(StepResult {
+                name: step.name.clone(),
+                outcome,
+            });
+        }
+        Ok(results)
+    }
+
+    fn check(
+        &self,
+        name: &str,
+        actual: &RgbaImage,
+        script: &VisualScript,
+    ) -> Result<StepOutcome, HeadlessError> {
+        let reference_path = self.references.join(format!("{}.png", name));
+        if self.update {
+            std::fs::create_dir_all(&self.references)?;
+            save_reference(actual, &reference_path)?;
+            return Ok(StepOutcome::Updated);
+        }
+
+        let outcome = if reference_path.exists() {
+            let reference = image::open(&reference_path)?.to_rgba8();
+            match compare_images(actual, &reference, script.tolerance) {
+                Some(diff) if diff.diff_pixels <= script.max_diff_pixels => StepOutcome::Passed,
+                Some(diff) => {
+                    std::fs::create_dir_all(&self.output)?;
+                    let diff_path = self.output.join(format!("{}.diff.png", name));
+                    diff.image
+                        .save_with_format(diff_path, image::ImageFormat::Png)?;
+                    StepOutcome::Failed {
+                        diff_pixels: diff.diff_pixels,
+                    }
+                }
+                None => StepOutcome::SizeMismatch {
+                    expected: reference.dimensions(),
+                    actual: actual.dimensions(),
+                },
+            }
+        } else {
+            StepOutcome::MissingReference
+        };
+
+        if outcome != StepOutcome::Passed {
+            std::fs::create_dir_all(&self.output)?;
+            let actual_path = self.output.join(format!("{}.actual.png", name));
+            actual.save_with_format(actual_path, image::ImageFormat::Png)?;
+        }
+        Ok(outcome)
+    }
+}
+
+/// Save a reference image as small as PNG allows, since references are
+/// committed with the bundle. Renders are opaque, so the alpha channel is
+/// left out.
+fn save_reference(image: &RgbaImage, path: &Path) -> Result<(), HeadlessError> {
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let encoder = PngEncoder::new_with_quality(file, CompressionType::Best, FilterType::Adaptive);
+    DynamicImage::ImageRgba8(image.clone())
+        .to_rgb8()
+        .write_with_encoder(encoder)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_images_tolerance() {
+        let reference = RgbaImage::from_pixel(4, 4, Rgba([100, 100, 100, 255]));
+        let mut actual = reference.clone();
+        actual.put_pixel(1, 1, Rgba([102, 100, 100, 255]));
+        actual.put_pixel(2, 2, Rgba([140, 100, 100, 255]));
+
+        let diff = compare_images(&actual, &reference, 2).unwrap();
+        assert_eq!(diff.diff_pixels, 1);
+        assert_eq!(*diff.image.get_pixel(2, 2), Rgba([255, 0, 0, 255]));
+        assert!(compare_images(&RgbaImage::new(2, 2), &reference, 0).is_none());
+    }
+
+    fn demo_bundle() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("demo_app.crix")
+    }
+
+    #[test]
+    fn test_demo_matches_committed_references() {
+        let output = std::env::temp_dir().join("crix-visual-demo-failures");
+        let test = VisualTest::new(demo_bundle()).with_output(&output);
+
+        for result in test.run().unwrap() {
+            assert_eq!(result.outcome, StepOutcome::Passed, "step {}", result.name);
+        }
+    }
+
+    #[test]
+    fn test_pointer_goes_through_hit_testing_at_scale() {
+        let bundle = AppBundle::load(&demo_bundle()).unwrap();
+        let mut session = VisualSession::new(bundle, Store::new(), 2).unwrap();
+        let (width, height) = session.app().view().size();
+        assert_eq!(session.render().dimensions(), (width * 2, height * 2));
+
+        // A point over the button hovers it, as the window's hit test decides
+        let button = session
+            .app()
+            .tree()
+            .find_by_name("calculate_button")
+            .unwrap();
+        let bounds = session.app().tree().get(button).unwrap().bounds;
+        session
+            .hover(&Target::Point(bounds.x + 1, bounds.y + 1))
+            .unwrap();
+        assert_eq!(session.app().tree().hovered(), Some(button));
+
+        // A group's center is one of its children, not the group
+        let group = Target::Part("current_fuel".to_string());
+        assert!(matches!(
+            session.click(&group),
+            Err(HeadlessError::InvalidScript(_))
+        ));
+    }
+}
@@ -1,37 +1,66 @@
 use std::rc::Rc;
 
 use winit::application::ApplicationHandler;
-use winit::dpi::PhysicalSize;
-use winit::event::WindowEvent;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{MouseScrollDelta, StartCause, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
+use winit::keyboard::{Key, ModifiersState};
 use winit::window::{Window, WindowAttributes, WindowId};
 
-use crate::core::App;
+use crate::core::{App, HitRegion, UiScale, WindowRequest};
 use crate::graphics::Renderer;
 
+/// Largest scale the zoom shortcuts go up to.
+const MAX_ZOOM: u32 = 8;
+
 struct AppState<A: App> {
     app: A,
     window: Rc<Window>,
     renderer: Renderer,
 }
 
+impl<A: App> AppState<A> {
+    /// Draw at `scale` and resize the window to fit the UI at that scale.
+    fn rescale(&mut self, scale: u32, ui_size: PhysicalSize<u32>, config: &RunConfig) {
+        self.renderer.set_scale(scale);
+        self.limit_size(scale, config);
+        let _ = self.window.request_inner_size(scaled(ui_size, scale));
+        self.window.request_redraw();
+    }
+
+    /// Keep the window between the configured sizes at `scale`.
+    fn limit_size(&self, scale: u32, config: &RunConfig) {
+        let limit = |size: Option<(u32, u32)>| {
+            size.map(|(width, height)| scaled(PhysicalSize::new(width, height), scale))
+        };
+        self.window.set_min_inner_size(limit(config.min_size));
+        self.window.set_max_inner_size(limit(config.max_size));
+    }
+}
+
 struct WinitHandler<A: App> {
     pending_app: Option<A>,
     context: softbuffer::Context<winit::event_loop::OwnedDisplayHandle>,
-    size: PhysicalSize<u32>,
-    resizable: bool,
-    title: String,
+    /// Size of the app's view in UI pixels.
+    ui_size: PhysicalSize<u32>,
+    config: RunConfig,
+    modifiers: ModifiersState,
     state: Option<AppState<A>>,
 }
 
 impl<A: App> WinitHandler<A> {
-    fn new(app: A, context: softbuffer::Context<winit::event_loop::OwnedDisplayHandle>, size: PhysicalSize<u32>, resizable: bool, title: String) -> Self {
+    fn new(
+        app: A,
+        context: softbuffer::Context<winit::event_loop::OwnedDisplayHandle>,
+        ui_size: PhysicalSize<u32>,
+        config: RunConfig,
+    ) -> Self {
         Self {
             pending_app: Some(app),
             context,
-            size,
-            resizable,
-            title,
+            ui_size,
+            config,
+            modifiers: ModifiersState::empty(),
             state: None,
         }
     }
@@ -43,10 +72,16 @@ impl<A: App> ApplicationHandler for WinitHandler<A> {
             return;
         };
 
+        let scale_factor = event_loop
+            .primary_monitor()
+            .map_or(1.0, |m| m.scale_factor());
+        let scale = self.config.scale.resolve(scale_factor);
         let attrs = WindowAttributes::default()
-            .with_inner_size(self.size)
-            .with_resizable(self.resizable)
-            .with_title(&self.title);
+            .with_inner_size(scaled(self.ui_size, scale))
+            .with_resizable(self.config.resizable)
+            .with_decorations(self.config.decorations)
+            .with_transparent(self.config.shape.is_some())
+            .with_title(&self.config.title);
 
         let window = Rc::new(
             event_loop
@@ -54,13 +89,37 @@ impl<A: App> ApplicationHandler for WinitHandler<A> {
                 .expect("Failed to create window"),
         );
 
-        let renderer = Renderer::new(&self.context, window.clone());
+        let mut renderer = Renderer::new(&self.context, window.clone());
+        renderer.set_shape(self.config.shape.clone());
 
-        self.state = Some(AppState {
+        let mut state = AppState {
             app,
             window,
             renderer,
-        });
+        };
+        // The window may have opened on a display other than the primary one
+        let scale = self.config.scale.resolve(state.window.scale_factor());
+        state.rescale(scale, self.ui_size, &self.config);
+        self.state = Some(state);
+    }
+
+    fn new_events(&mut self, _event_loop: &ActiveEventLoop, cause: StartCause) {
+        // A scheduled frame (see `about_to_wait`) is due
+        if let StartCause::ResumeTimeReached { .. } = cause
+            && let Some(state) = &self.state
+        {
+            state.window.request_redraw();
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        // Sleep until the next input event, or until the app's next
+        // animation frame if it has one scheduled.
+        let next_frame = self.state.as_ref().and_then(|state| state.app.next_frame());
+        match next_frame {
+            Some(deadline) => event_loop.set_control_flow(ControlFlow::WaitUntil(deadline)),
+            None => event_loop.set_control_flow(ControlFlow::Wait),
+        }
     }
 
     fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
@@ -73,10 +132,8 @@ impl<A: App> ApplicationHandler for WinitHandler<A> {
         &mut self,
         event_loop: &ActiveEventLoop,
         window_id: WindowId,
-        event: WindowEvent,
+        mut event: WindowEvent,
     ) {
-        event_loop.set_control_flow(ControlFlow::Wait);
-
         let Some(state) = &mut self.state else {
             return;
         };
@@ -85,11 +142,50 @@ impl<A: App> ApplicationHandler for WinitHandler<A> {
             return;
         }
 
-        match &event {
+        match &mut event {
+            WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                inner_size_writer,
+            } => {
+                let scale = self.config.scale.resolve(*scale_factor);
+                state.renderer.set_scale(scale);
+                state.limit_size(scale, &self.config);
+                let _ = inner_size_writer.request_inner_size(scaled(self.ui_size, scale));
+            }
+            WindowEvent::Resized(size) if size.width > 0 && size.height > 0 => {
+                // Remember the size the user picked so zooming keeps it
+                let scale = state.renderer.scale();
+                self.ui_size =
+                    PhysicalSize::new(size.width.div_ceil(scale), size.height.div_ceil(scale));
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
+            }
+            WindowEvent::KeyboardInput { event: key, .. }
+                if key.state.is_pressed() && self.modifiers.control_key() =>
+            {
+                // Zoom shortcuts are handled here and not passed to the app
+                let current = state.renderer.scale();
+                let zoom = match key.logical_key.as_ref() {
+                    Key::Character("=" | "+") => Some(UiScale::Fixed((current + 1).min(MAX_ZOOM))),
+                    Key::Character("-") => Some(UiScale::Fixed(current.saturating_sub(1).max(1))),
+                    Key::Character("0") => Some(UiScale::Auto),
+                    _ => None,
+                };
+                if let Some(zoom) = zoom {
+                    self.config.scale = zoom;
+                    let scale = zoom.resolve(state.window.scale_factor());
+                    state.rescale(scale, self.ui_size, &self.config);
+                    return;
+                }
+            }
             WindowEvent::RedrawRequested => {
                 let size = state.window.inner_size();
                 state.renderer.resize(size.width, size.height);
-                state.renderer.render(state.app.view());
+                match state.app.take_damage() {
+                    Some(damage) => state.renderer.render_damaged(state.app.view(), &damage),
+                    None => state.renderer.render(state.app.view()),
+                }
             }
             WindowEvent::CloseRequested => {
                 event_loop.exit();
@@ -97,9 +193,62 @@ impl<A: App> ApplicationHandler for WinitHandler<A> {
             _ => {}
         }
 
+        let event = to_ui_space(event, state.renderer.scale());
         if state.app.on_event(&event) {
             state.window.request_redraw();
         }
+
+        for request in state.app.take_window_requests() {
+            match request {
+                WindowRequest::Drag => {
+                    // Fails when no button is held; there is nothing to drag then
+                    let _ = state.window.drag_window();
+                }
+                WindowRequest::Minimize => state.window.set_minimized(true),
+                WindowRequest::Close => event_loop.exit(),
+                WindowRequest::SetScale(mode) => {
+                    self.config.scale = mode;
+                    let scale = mode.resolve(state.window.scale_factor());
+                    state.rescale(scale, self.ui_size, &self.config);
+                }
+            }
+        }
+    }
+}
+
+/// A size in UI pixels, in screen pixels.
+fn scaled(size: PhysicalSize<u32>, scale: u32) -> PhysicalSize<u32> {
+    PhysicalSize::new(size.width * scale, size.height * scale)
+}
+
+/// Convert pointer positions and window sizes from screen pixels to UI pixels.
+pub(super) fn to_ui_space(event: WindowEvent, scale: u32) -> WindowEvent {
+    if let WindowEvent::Resized(size) = event {
+        let size = PhysicalSize::new(size.width.div_ceil(scale), size.height.div_ceil(scale));
+        return WindowEvent::Resized(size);
+    }
+    let scale = scale as f64;
+    match event {
+        WindowEvent::CursorMoved {
+            device_id,
+            position,
+        } => WindowEvent::CursorMoved {
+            device_id,
+            position: PhysicalPosition::new(position.x / scale, position.y / scale),
+        },
+        WindowEvent::MouseWheel {
+            device_id,
+            delta: MouseScrollDelta::PixelDelta(delta),
+            phase,
+        } => WindowEvent::MouseWheel {
+            device_id,
+            delta: MouseScrollDelta::PixelDelta(PhysicalPosition::new(
+                delta.x / scale,
+                delta.y / scale,
+            )),
+            phase,
+        },
+        other => other,
     }
 }
 
@@ -107,6 +256,19 @@ impl<A: App> ApplicationHandler for WinitHandler<A> {
 pub struct RunConfig {
     pub resizable: bool,
     pub title: String,
+    /// Whether the OS draws a title bar and border around the window.
+    pub decorations: bool,
+    /// The window's shape within its bounds. Pixels outside it are left
+    /// transparent where the platform composites transparent windows with
+    /// an alpha channel, and black elsewhere.
+    pub shape: Option<HitRegion>,
+    /// How many screen pixels each UI pixel covers. Ctrl+= and Ctrl+- zoom
+    /// in and out at runtime, and Ctrl+0 goes back to `Auto`.
+    pub scale: UiScale,
+    /// Smallest (width, height) in UI pixels a resizable window can take.
+    pub min_size: Option<(u32, u32)>,
+    /// Largest (width, height) in UI pixels a resizable window can take.
+    pub max_size: Option<(u32, u32)>,
 }
 
 impl Default for RunConfig {
@@ -114,6 +276,11 @@ impl Default for RunConfig {
         Self {
             resizable: false,
             title: String::from("Crix"),
+            decorations: true,
+            shape: None,
+            scale: UiScale::Auto,
+            min_size: None,
+            max_size: None,
         }
     }
 }
@@ -124,10 +291,42 @@ impl RunConfig {
         self.title = title.into();
         self
     }
+
+    /// Let the user resize the window. The app sees a `Resized` event in UI
+    /// pixels and lays itself out again.
+    pub fn with_resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    /// Keep a resizable window between `min` and `max` (width, height) in UI pixels.
+    pub fn with_size_limits(mut self, min: Option<(u32, u32)>, max: Option<(u32, u32)>) -> Self {
+        self.min_size = min;
+        self.max_size = max;
+        self
+    }
+
+    /// Show or hide the OS title bar and border.
+    pub fn with_decorations(mut self, decorations: bool) -> Self {
+        self.decorations = decorations;
+        self
+    }
+
+    /// Set how the UI is scaled up on screen.
+    pub fn with_scale(mut self, scale: UiScale) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Give the window a transparent background outside `shape`.
+    pub fn with_shape(mut self, shape: HitRegion) -> Self {
+        self.shape = Some(shape);
+        self
+    }
 }
 
 /// Run an application with the given configuration.
-/// The window size is determined by the app's view size.
+/// The window size is the app's view size times the UI scale.
 pub fn run<A: App + 'static>(app: A, config: RunConfig) {
     let event_loop = EventLoop::new().expect("Failed to create event loop");
     let context = softbuffer::Context::new(event_loop.owned_display_handle())
@@ -135,8 +334,8 @@ pub fn run<A: App + 'static>(app: A, config: RunConfig) {
 
     // Get the size from the app's view
     let (width, height) = app.view().size();
-    let size = PhysicalSize::new(width, height);
-    let mut handler = WinitHandler::new(app, context, size, config.resizable, config.title);
+    let ui_size = PhysicalSize::new(width, height);
+    let mut handler = WinitHandler::new(app, context, ui_size, config);
 
     event_loop.run_app(&mut handler).expect("Event loop failed");
 }
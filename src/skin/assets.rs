@@ -1,14 +1,29 @@
 use std::collections::HashMap;
-use std::path::Path;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use image::{ImageReader, RgbImage};
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::{imageops, AnimationDecoder, Delay, DynamicImage, ImageReader, RgbaImage};
 
-use super::types::{Skin, SkinError, SkinWindow};
+use crate::graphics::text::DEFAULT_FONT;
+use crate::graphics::{BitmapFont, Font, FontRegistry, Sprite};
+
+use super::types::{Skin, SkinError, SkinFont, SkinWindow};
+
+/// Frames of a decoded image with their display times.
+type Frames = Vec<(RgbaImage, Duration)>;
+
+/// Frame time used for frame strips and GIFs with no (or a tiny) delay.
+const DEFAULT_FRAME_DURATION: Duration = Duration::from_millis(100);
 
 /// A skin with all assets loaded and ready to use.
 pub struct LoadedSkin {
     pub skin: Skin,
-    images: HashMap<String, RgbImage>,
+    sprites: HashMap<String, Sprite>,
+    fonts: FontRegistry,
 }
 
 impl LoadedSkin {
@@ -16,17 +31,198 @@ impl LoadedSkin {
     pub fn load(path: &Path) -> Result<Self, SkinError> {
         let skin = Skin::load(path)?;
 
-        let mut images = HashMap::new();
+        let mut sprites = HashMap::new();
+        // Decoded files, so sprite sheets shared by many assets load once
+        let mut files: HashMap<PathBuf, Frames> = HashMap::new();
 
         // Load all image assets
-        for (key, asset_path) in &skin.assets {
-            let reader = ImageReader::open(asset_path)?;
-            let img = reader.decode()?;
-            let rgb = img.to_rgb8();
-            images.insert(key.clone(), rgb);
+        for (key, asset) in &skin.assets {
+            if !files.contains_key(&asset.path) {
+                files.insert(asset.path.clone(), Self::decode(&asset.path)?);
+            }
+            let source = &files[&asset.path];
+
+            let mut frames = match asset.rect {
+                Some(rect) => source
+                    .iter()
+                    .map(|(img, delay)| Ok((Self::crop(key, img, rect)?, *delay)))
+                    .collect::<Result<Frames, SkinError>>()?,
+                None => source.clone(),
+            };
+            if let Some(count) = asset.frames {
+                // Splitting only the first frame would drop the rest
+                if frames.len() > 1 {
+                    return Err(SkinError::InvalidAsset(format!(
+                        "'{}' sets 'frames' on an image that is already animated",
+                        key
+                    )));
+                }
+                frames = Self::split_strip(key, &frames[0].0, count)?;
+            }
+            if let Some(color) = asset.color_key {
+                for (frame, _) in &mut frames {
+                    Self::apply_color_key(frame, color);
+                }
+            }
+            if let Some(ms) = asset.frame_duration {
+                for (_, delay) in &mut frames {
+                    *delay = Duration::from_millis(ms as u64);
+                }
+            }
+
+            let mut sprite = Sprite::animated(frames, asset.playback);
+            if let Some(slice) = asset.nine_slice {
+                sprite = sprite.with_nine_slice(slice);
+            }
+            sprites.insert(key.clone(), sprite);
+        }
+
+        let mut fonts = FontRegistry::new();
+        for (key, font) in &skin.fonts {
+            let font = match font {
+                SkinFont::File(path) => Font::load(path)?,
+                SkinFont::Grid {
+                    sheet,
+                    cell_width,
+                    cell_height,
+                    chars,
+                } => {
+                    let sheet = ImageReader::open(sheet)?.decode()?.to_rgba8();
+                    let grid = BitmapFont::from_grid(&sheet, *cell_width, *cell_height, chars)?;
+                    Font::from_bitmap(grid)
+                }
+            };
+            fonts.insert(key.clone(), font);
+        }
+        // The default font may come from the bundle, after the skin is loaded
+        if let Some(key) = skin
+            .font_fallback
+            .iter()
+            .find(|key| *key != DEFAULT_FONT && !skin.fonts.contains_key(*key))
+        {
+            return Err(SkinError::FontNotFound(key.clone()));
+        }
+        fonts.set_fallbacks(skin.font_fallback.clone());
+
+        Ok(Self {
+            skin,
+            sprites,
+            fonts,
+        })
+    }
+
+    /// Decode an image file. Animated GIFs and APNGs yield all their frames;
+    /// everything else yields a single frame.
+    fn decode(path: &Path) -> Result<Frames, SkinError> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+
+        let frames = match extension.as_deref() {
+            Some("gif") => {
+                let decoder = GifDecoder::new(BufReader::new(File::open(path)?))?;
+                decoder.into_frames().collect_frames()?
+            }
+            Some("png") | Some("apng") => {
+                let decoder = PngDecoder::new(BufReader::new(File::open(path)?))?;
+                if !decoder.is_apng()? {
+                    let img = DynamicImage::from_decoder(decoder)?;
+                    return Ok(vec![(img.to_rgba8(), Duration::ZERO)]);
+                }
+                decoder.apng()?.into_frames().collect_frames()?
+            }
+            _ => Vec::new(),
+        };
+
+        if frames.is_empty() {
+            let img = ImageReader::open(path)?.decode()?;
+            return Ok(vec![(img.to_rgba8(), Duration::ZERO)]);
+        }
+
+        Ok(frames
+            .into_iter()
+            .map(|frame| {
+                let delay = frame_delay(frame.delay());
+                (frame.into_buffer(), delay)
+            })
+            .collect())
+    }
+
+    /// Split a horizontal strip of equally sized frames.
+    fn split_strip(key: &str, strip: &RgbaImage, count: u32) -> Result<Frames, SkinError> {
+        let frame_width = strip.width() / count;
+        if frame_width == 0 {
+            return Err(SkinError::InvalidAsset(format!(
+                "'{}' is {}px wide, too narrow for {} frames",
+                key,
+                strip.width(),
+                count
+            )));
         }
+        if !strip.width().is_multiple_of(count) {
+            return Err(SkinError::InvalidAsset(format!(
+                "'{}' is {}px wide, which doesn't split into {} equal frames",
+                key,
+                strip.width(),
+                count
+            )));
+        }
+
+        Ok((0..count)
+            .map(|i| {
+                let x = i * frame_width;
+                let frame = imageops::crop_imm(strip, x, 0, frame_width, strip.height());
+                (frame.to_image(), DEFAULT_FRAME_DURATION)
+            })
+            .collect())
+    }
 
-        Ok(Self { skin, images })
+    /// Make every pixel of exactly `color` (0xRRGGBB) fully transparent.
+    fn apply_color_key(image: &mut RgbaImage, color: u32) {
+        let [_, r, g, b] = color.to_be_bytes();
+        for pixel in image.pixels_mut() {
+            if pixel.0[..3] == [r, g, b] {
+                // Black rather than the key color, so scaled edges don't tint
+                pixel.0 = [0, 0, 0, 0];
+            }
+        }
+    }
+
+    /// Cut an atlas sub-rectangle out of a sprite sheet.
+    fn crop(
+        key: &str,
+        sheet: &RgbaImage,
+        [x, y, width, height]: [u32; 4],
+    ) -> Result<RgbaImage, SkinError> {
+        let fits_x = x
+            .checked_add(width)
+            .is_some_and(|right| right <= sheet.width());
+        let fits_y = y
+            .checked_add(height)
+            .is_some_and(|bottom| bottom <= sheet.height());
+        if !fits_x || !fits_y {
+            return Err(SkinError::InvalidAsset(format!(
+                "'{}' rect [{}, {}, {}, {}] is outside its {}x{} sheet",
+                key,
+                x,
+                y,
+                width,
+                height,
+                sheet.width(),
+                sheet.height()
+            )));
+        }
+        Ok(imageops::crop_imm(sheet, x, y, width, height).to_image())
+    }
+
+    /// Fonts declared by the skin (plus any registered by the bundle).
+    pub fn fonts(&self) -> &FontRegistry {
+        &self.fonts
+    }
+
+    pub fn fonts_mut(&mut self) -> &mut FontRegistry {
+        &mut self.fonts
     }
 
     /// Get the window configuration from the skin.
@@ -40,7 +236,65 @@ impl LoadedSkin {
     }
 
     /// Get an image by asset key.
-    pub fn get_image(&self, key: &str) -> Option<&RgbImage> {
-        self.images.get(key)
+    pub fn get_image(&self, key: &str) -> Option<&RgbaImage> {
+        self.sprites.get(key).map(Sprite::image)
+    }
+
+    /// Get a drawable sprite (including any nine-slice settings) by asset key.
+    pub fn get_sprite(&self, key: &str) -> Option<&Sprite> {
+        self.sprites.get(key)
+    }
+}
+
+/// Convert a decoder frame delay, treating near-zero delays like browsers do.
+fn frame_delay(delay: Delay) -> Duration {
+    let (numer, denom) = delay.numer_denom_ms();
+    let ms = numer.checked_div(denom).unwrap_or(0);
+    if ms <= 10 {
+        DEFAULT_FRAME_DURATION
+    } else {
+        Duration::from_millis(ms as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn test_crop_extracts_sheet_region() {
+        let mut sheet = RgbaImage::new(4, 2);
+        sheet.put_pixel(2, 1, Rgba([255, 0, 0, 255]));
+
+        let cropped = LoadedSkin::crop("item", &sheet, [2, 0, 2, 2]).unwrap();
+        assert_eq!(cropped.dimensions(), (2, 2));
+        assert_eq!(cropped.get_pixel(0, 1).0, [255, 0, 0, 255]);
+
+        assert!(LoadedSkin::crop("item", &sheet, [3, 0, 2, 2]).is_err());
+    }
+
+    #[test]
+    fn test_split_strip_into_frames() {
+        let mut strip = RgbaImage::new(6, 2);
+        strip.put_pixel(4, 0, Rgba([0, 255, 0, 255]));
+
+        let frames = LoadedSkin::split_strip("spinner", &strip, 3).unwrap();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[2].0.dimensions(), (2, 2));
+        assert_eq!(frames[2].0.get_pixel(0, 0).0, [0, 255, 0, 255]);
+
+        assert!(LoadedSkin::split_strip("spinner", &strip, 7).is_err());
+        assert!(LoadedSkin::split_strip("spinner", &strip, 4).is_err());
+    }
+
+    #[test]
+    fn test_color_key_becomes_transparent() {
+        let mut image = RgbaImage::from_pixel(2, 1, Rgba([255, 0, 255, 255]));
+        image.put_pixel(1, 0, Rgba([255, 0, 254, 255]));
+
+        LoadedSkin::apply_color_key(&mut image, 0xFF00FF);
+        assert_eq!(image.get_pixel(0, 0).0, [0, 0, 0, 0]);
+        assert_eq!(image.get_pixel(1, 0).0, [255, 0, 254, 255]);
     }
 }
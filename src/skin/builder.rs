@@ -1,9 +1,13 @@
-use crate::core::{Rect, UiTree, Widget};
+use crate::core::{HitMask, HitRegion, NodeId, Rect, UiTree, Widget};
+use crate::graphics::Font;
 use crate::widgets::Container;
 
 use super::assets::LoadedSkin;
-use super::types::{PartType, SkinError, SkinPart, SkinWindow};
-use super::widgets::{Checkbox, DirectoryPicker, FilePicker, SkinButton, SkinImage, SkinVScroll, StaticText, TextInput};
+use super::types::{HitType, PartType, SkinError, SkinPart, SkinWindow};
+use super::widgets::{
+    Checkbox, DirectoryPicker, DragRegion, FilePicker, SkinButton, SkinImage, SkinVScroll,
+    StaticText, TextInput,
+};
 
 /// Builds a UiTree from a loaded skin.
 pub struct SkinBuilder;
@@ -26,21 +30,157 @@ impl SkinBuilder {
 
         // Create widgets and add to tree as children of root
         for part in parts {
-            let widget = Self::create_widget(part, skin)?;
-            let bounds = Rect::new(part.x, part.y, part.width, part.height);
-
-            let node_id = tree.add_boxed(widget, Some(root_id));
-            tree.set_bounds(node_id, bounds);
+            Self::add_part(&mut tree, part, root_id, (0, 0), skin)?;
         }
+        tree.apply_layout(root_id);
+        // Anchors keep the distances measured once everything is in place
+        Self::set_anchors(&mut tree, &skin.skin.parts, false);
 
         Ok((tree, skin.skin.window.clone()))
     }
 
+    /// Anchor every part that isn't placed by a layout, to its top-left
+    /// corner unless it says otherwise, so groups carry their contents along.
+    fn set_anchors(tree: &mut UiTree, parts: &[SkinPart], in_layout: bool) {
+        for part in parts {
+            if !in_layout && let Some(id) = tree.find_by_name(&part.id) {
+                tree.set_anchor(id, Some(part.anchor.unwrap_or_default()));
+            }
+            let layout = matches!(part.part_type, PartType::Layout(_));
+            Self::set_anchors(tree, &part.children, layout);
+        }
+    }
+
+    /// Add a part and the parts inside it under `parent`, whose top-left
+    /// corner is at `origin`.
+    fn add_part(
+        tree: &mut UiTree,
+        part: &SkinPart,
+        parent: NodeId,
+        origin: (i32, i32),
+        skin: &LoadedSkin,
+    ) -> Result<NodeId, SkinError> {
+        let widget = Self::create_widget(part, skin)?;
+        let natural_size = widget.preferred_size();
+        let (x, y) = (origin.0 + part.x, origin.1 + part.y);
+
+        let node_id = tree.add_boxed(widget, Some(parent));
+        tree.set_name(node_id, &part.id);
+        tree.set_hit_region(node_id, Self::hit_region(part, skin)?);
+
+        // Layout children keep the order they are listed in, others go by z
+        let mut children: Vec<_> = part.children.iter().collect();
+        if !matches!(part.part_type, PartType::Layout(_)) {
+            children.sort_by_key(|child| child.z);
+        }
+        let mut child_bounds = Vec::new();
+        for child in children {
+            let child_id = Self::add_part(tree, child, node_id, (x, y), skin)?;
+            child_bounds.push(
+                tree.get(child_id)
+                    .map(|node| *node.bounds())
+                    .unwrap_or_default(),
+            );
+        }
+
+        // Missing sizes come from the widget, or from the children of a
+        // layout or group
+        let (width, height) = match &part.part_type {
+            PartType::Layout(layout) => {
+                tree.set_layout(node_id, Some(layout.clone()));
+                let sizes: Vec<_> = child_bounds.iter().map(|b| (b.width, b.height)).collect();
+                layout.measure(&sizes)
+            }
+            PartType::Group => child_bounds.iter().fold((0, 0), |(w, h), b| {
+                (
+                    w.max((b.right() - x).max(0) as u32),
+                    h.max((b.bottom() - y).max(0) as u32),
+                )
+            }),
+            _ => natural_size,
+        };
+        let width = if part.width > 0 { part.width } else { width };
+        let height = if part.height > 0 { part.height } else { height };
+        tree.set_bounds(node_id, Rect::new(x, y, width, height));
+        tree.set_visible(node_id, part.visible);
+        tree.set_clip(node_id, part.clip);
+
+        Ok(node_id)
+    }
+
+    /// The window's shape: the opaque pixels of the `shape` asset, stretched
+    /// over the window. `None` for rectangular windows.
+    pub fn window_shape(skin: &LoadedSkin) -> Result<Option<HitRegion>, SkinError> {
+        let Some(key) = &skin.skin.window.shape else {
+            return Ok(None);
+        };
+        let image = skin
+            .get_image(key)
+            .ok_or_else(|| SkinError::AssetNotFound(key.clone()))?;
+        Ok(Some(HitRegion::Mask(HitMask::from_alpha(image))))
+    }
+
+    /// The region a part's `hit` narrows its bounds to, if any.
+    fn hit_region(part: &SkinPart, skin: &LoadedSkin) -> Result<Option<HitRegion>, SkinError> {
+        let Some(hit) = &part.hit else {
+            return Ok(None);
+        };
+        let mask = |key: &str| {
+            skin.get_image(key)
+                .map(|image| Some(HitRegion::Mask(HitMask::from_alpha(image))))
+                .ok_or_else(|| SkinError::AssetNotFound(key.to_string()))
+        };
+
+        match &hit.hit_type {
+            HitType::Rect => Ok(None),
+            HitType::Ellipse => Ok(Some(HitRegion::Ellipse)),
+            HitType::Polygon(points) => Ok(Some(HitRegion::Polygon(points.clone()))),
+            HitType::Mask { asset } => mask(asset),
+            HitType::Alpha { asset } => {
+                match asset.as_deref().or_else(|| Self::primary_asset(part)) {
+                    Some(key) => mask(key),
+                    None => Err(SkinError::InvalidHit(format!(
+                        "'{}' has no asset to test alpha against",
+                        part.id
+                    ))),
+                }
+            }
+        }
+    }
+
+    /// The image a part is drawn with in its resting state.
+    fn primary_asset(part: &SkinPart) -> Option<&str> {
+        match &part.part_type {
+            PartType::Image { asset } => Some(asset),
+            PartType::Button => part.draw.as_ref().map(|d| d.normal.as_str()),
+            PartType::TextInput => part.text_input_draw.as_ref().map(|d| d.normal.as_str()),
+            PartType::Checkbox => part.checkbox_draw.as_ref().map(|d| d.unchecked.as_str()),
+            _ => None,
+        }
+    }
+
+    /// The font a part names, or the skin's default font. Skins loaded
+    /// outside a bundle may have no default font; their text isn't drawn.
+    fn font(part: &SkinPart, skin: &LoadedSkin) -> Result<Font, SkinError> {
+        match &part.font {
+            Some(key) => skin
+                .fonts()
+                .resolve(key)
+                .ok_or_else(|| SkinError::FontNotFound(key.clone())),
+            None => Ok(skin.fonts().default_font().unwrap_or_default()),
+        }
+    }
+
+    /// The part's font size, or the skin's default size.
+    fn font_size(part: &SkinPart, skin: &LoadedSkin) -> f32 {
+        part.font_size.unwrap_or(skin.fonts().default_size())
+    }
+
     fn create_widget(part: &SkinPart, skin: &LoadedSkin) -> Result<Box<dyn Widget>, SkinError> {
         match &part.part_type {
             PartType::Image { asset } => {
                 let image = skin
-                    .get_image(asset)
+                    .get_sprite(asset)
                     .ok_or_else(|| SkinError::AssetNotFound(asset.clone()))?;
                 Ok(Box::new(SkinImage::new(image.clone())))
             }
@@ -51,13 +191,13 @@ impl SkinBuilder {
                     .ok_or_else(|| SkinError::MissingDrawSection(part.id.clone()))?;
 
                 let normal = skin
-                    .get_image(&draw.normal)
+                    .get_sprite(&draw.normal)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.normal.clone()))?;
                 let hover = skin
-                    .get_image(&draw.hover)
+                    .get_sprite(&draw.hover)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.hover.clone()))?;
                 let pressed = skin
-                    .get_image(&draw.pressed)
+                    .get_sprite(&draw.pressed)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.pressed.clone()))?;
 
                 Ok(Box::new(SkinButton::new(
@@ -74,15 +214,18 @@ impl SkinBuilder {
                     .ok_or_else(|| SkinError::MissingDrawSection(part.id.clone()))?;
 
                 let normal = skin
-                    .get_image(&draw.normal)
+                    .get_sprite(&draw.normal)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.normal.clone()))?;
                 let hover = skin
-                    .get_image(&draw.hover)
+                    .get_sprite(&draw.hover)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.hover.clone()))?;
                 let focused = skin
-                    .get_image(&draw.focused)
+                    .get_sprite(&draw.focused)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.focused.clone()))?;
-                let invalid = draw.invalid.as_ref().and_then(|key| skin.get_image(key).cloned());
+                let invalid = draw
+                    .invalid
+                    .as_ref()
+                    .and_then(|key| skin.get_sprite(key).cloned());
 
                 let mut text_input = TextInput::new(
                     normal.clone(),
@@ -97,12 +240,13 @@ impl SkinBuilder {
                 if let Some(color) = part.text_color {
                     text_input = text_input.with_text_color(color);
                 }
+                text_input = text_input.with_text_effects(part.text_effects);
                 if let Some(padding) = part.padding {
                     text_input = text_input.with_padding(padding);
                 }
-                if let Some(size) = part.font_size {
-                    text_input = text_input.with_font_size(size);
-                }
+                text_input = text_input
+                    .with_font(Self::font(part, skin)?)
+                    .with_font_size(Self::font_size(part, skin));
                 if let Some(max) = part.max_length {
                     text_input = text_input.with_max_length(max);
                 }
@@ -119,18 +263,28 @@ impl SkinBuilder {
                 let content = part.content.clone().unwrap_or_default();
                 let mut static_text = StaticText::new(content);
 
-                if let Some(size) = part.font_size {
-                    static_text = static_text.with_font_size(size);
-                }
+                static_text = static_text
+                    .with_font(Self::font(part, skin)?)
+                    .with_font_size(Self::font_size(part, skin));
                 if let Some(color) = part.text_color {
                     static_text = static_text.with_text_color(color);
                 }
+                static_text = static_text.with_text_effects(part.text_effects);
                 if let Some(align) = part.text_align {
                     static_text = static_text.with_text_align(align);
                 }
                 if let Some(valign) = part.vertical_align {
                     static_text = static_text.with_vertical_align(valign);
                 }
+                if let Some(wrap) = part.wrap {
+                    static_text = static_text.with_wrap(wrap);
+                }
+                if let Some(spacing) = part.line_spacing {
+                    static_text = static_text.with_line_spacing(spacing);
+                }
+                if let Some(max_lines) = part.max_lines {
+                    static_text = static_text.with_max_lines(max_lines);
+                }
                 if let Some(padding) = part.padding {
                     static_text = static_text.with_padding(padding);
                 }
@@ -147,10 +301,10 @@ impl SkinBuilder {
                     .ok_or_else(|| SkinError::MissingDrawSection(format!("{} (scrollbar)", part.id)))?;
 
                 let track = skin
-                    .get_image(&scrollbar.track)
+                    .get_sprite(&scrollbar.track)
                     .ok_or_else(|| SkinError::AssetNotFound(scrollbar.track.clone()))?;
                 let thumb = skin
-                    .get_image(&scrollbar.thumb)
+                    .get_sprite(&scrollbar.thumb)
                     .ok_or_else(|| SkinError::AssetNotFound(scrollbar.thumb.clone()))?;
 
                 let mut scroll = SkinVScroll::new(
@@ -180,16 +334,16 @@ impl SkinBuilder {
                     .ok_or_else(|| SkinError::MissingDrawSection(part.id.clone()))?;
 
                 let normal = skin
-                    .get_image(&draw.normal)
+                    .get_sprite(&draw.normal)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.normal.clone()))?;
                 let hover = skin
-                    .get_image(&draw.hover)
+                    .get_sprite(&draw.hover)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.hover.clone()))?;
                 let button_normal = skin
-                    .get_image(&draw.button_normal)
+                    .get_sprite(&draw.button_normal)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.button_normal.clone()))?;
                 let button_hover = skin
-                    .get_image(&draw.button_hover)
+                    .get_sprite(&draw.button_hover)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.button_hover.clone()))?;
 
                 let mut picker = DirectoryPicker::new(
@@ -202,12 +356,13 @@ impl SkinBuilder {
                 if let Some(color) = part.text_color {
                     picker = picker.with_text_color(color);
                 }
+                picker = picker.with_text_effects(part.text_effects);
                 if let Some(padding) = part.padding {
                     picker = picker.with_padding(padding);
                 }
-                if let Some(size) = part.font_size {
-                    picker = picker.with_font_size(size);
-                }
+                picker = picker
+                    .with_font(Self::font(part, skin)?)
+                    .with_font_size(Self::font_size(part, skin));
                 if let Some(binding) = &part.binding {
                     picker = picker.with_binding(binding.clone());
                 }
@@ -221,31 +376,31 @@ impl SkinBuilder {
                     .ok_or_else(|| SkinError::MissingDrawSection(part.id.clone()))?;
 
                 let picker_normal = skin
-                    .get_image(&draw.picker_normal)
+                    .get_sprite(&draw.picker_normal)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.picker_normal.clone()))?;
                 let picker_hover = skin
-                    .get_image(&draw.picker_hover)
+                    .get_sprite(&draw.picker_hover)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.picker_hover.clone()))?;
                 let picker_btn_normal = skin
-                    .get_image(&draw.picker_btn_normal)
+                    .get_sprite(&draw.picker_btn_normal)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.picker_btn_normal.clone()))?;
                 let picker_btn_hover = skin
-                    .get_image(&draw.picker_btn_hover)
+                    .get_sprite(&draw.picker_btn_hover)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.picker_btn_hover.clone()))?;
                 let track = skin
-                    .get_image(&draw.track)
+                    .get_sprite(&draw.track)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.track.clone()))?;
                 let thumb = skin
-                    .get_image(&draw.thumb)
+                    .get_sprite(&draw.thumb)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.thumb.clone()))?;
                 let item_normal = skin
-                    .get_image(&draw.item_normal)
+                    .get_sprite(&draw.item_normal)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.item_normal.clone()))?;
                 let item_hover = skin
-                    .get_image(&draw.item_hover)
+                    .get_sprite(&draw.item_hover)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.item_hover.clone()))?;
                 let item_selected = skin
-                    .get_image(&draw.item_selected)
+                    .get_sprite(&draw.item_selected)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.item_selected.clone()))?;
 
                 let mut picker = FilePicker::new(
@@ -262,12 +417,16 @@ impl SkinBuilder {
                     item_selected.clone(),
                 );
 
+                picker = picker
+                    .with_font(Self::font(part, skin)?)
+                    .with_font_size(Self::font_size(part, skin));
                 if let Some(ref filter) = part.filter {
                     picker = picker.with_filter(filter.clone());
                 }
                 if let Some(color) = part.text_color {
                     picker = picker.with_text_color(color);
                 }
+                picker = picker.with_text_effects(part.text_effects);
                 if let Some(padding) = part.padding {
                     picker = picker.with_padding(padding);
                 }
@@ -287,10 +446,10 @@ impl SkinBuilder {
                     .ok_or_else(|| SkinError::MissingDrawSection(part.id.clone()))?;
 
                 let unchecked = skin
-                    .get_image(&draw.unchecked)
+                    .get_sprite(&draw.unchecked)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.unchecked.clone()))?;
                 let checked = skin
-                    .get_image(&draw.checked)
+                    .get_sprite(&draw.checked)
                     .ok_or_else(|| SkinError::AssetNotFound(draw.checked.clone()))?;
 
                 let mut checkbox = Checkbox::new(unchecked.clone(), checked.clone());
@@ -301,9 +460,10 @@ impl SkinBuilder {
                 if let Some(color) = part.text_color {
                     checkbox = checkbox.with_text_color(color);
                 }
-                if let Some(size) = part.font_size {
-                    checkbox = checkbox.with_font_size(size);
-                }
+                checkbox = checkbox.with_text_effects(part.text_effects);
+                checkbox = checkbox
+                    .with_font(Self::font(part, skin)?)
+                    .with_font_size(Self::font_size(part, skin));
                 if let Some(padding) = part.padding {
                     checkbox = checkbox.with_padding(padding);
                 }
@@ -316,6 +476,10 @@ impl SkinBuilder {
 
                 Ok(Box::new(checkbox))
             }
+            PartType::DragRegion => Ok(Box::new(DragRegion::new())),
+            PartType::Layout(_) | PartType::Group => {
+                Ok(Box::new(Container::transparent(part.width, part.height)))
+            }
         }
     }
 }
@@ -0,0 +1,245 @@
+//! Reusable part templates from the `components` section of `skin.json`.
+//!
+//! A component is a part without an `id` whose strings may contain
+//! `${name}` placeholders. A part with a `component` field is replaced by
+//! the template, filled in with its `args` and the template's `params`
+//! defaults; its own fields (`id`, `x`, `y`, ...) override the template's.
+//! Ids of the parts inside get the instance id as a prefix
+//! (`fuel.label`), so every instance's parts stay unique.
+//!
+//! Expansion works on the raw JSON before parts are parsed, so a field
+//! holding nothing but a placeholder takes the argument as is, whether
+//! that's a string, a number or `null`.
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+use super::types::SkinError;
+
+/// How deeply components may use other components before we assume one
+/// uses itself.
+const MAX_DEPTH: usize = 16;
+
+/// The skin's components by name.
+pub(super) struct Components(pub(super) HashMap<String, Map<String, Value>>);
+
+impl Components {
+    /// Replace every component instance in `parts`, at any depth, with its
+    /// expanded template.
+    pub(super) fn expand_all(&self, parts: Vec<Value>) -> Result<Vec<Value>, SkinError> {
+        self.expand_parts(parts, 0)
+    }
+
+    fn expand_parts(&self, parts: Vec<Value>, depth: usize) -> Result<Vec<Value>, SkinError> {
+        parts
+            .into_iter()
+            .map(|part| self.expand(part, depth))
+            .collect()
+    }
+
+    fn expand(&self, part: Value, depth: usize) -> Result<Value, SkinError> {
+        // Anything that isn't an object is reported when the part is parsed
+        let Value::Object(mut part) = part else {
+            return Ok(part);
+        };
+        let mut depth = depth;
+        if let Some(name) = part.remove("component") {
+            part = self.instantiate(part, &name, depth)?;
+            depth += 1;
+        }
+
+        if let Some(Value::Array(children)) = part.get_mut("parts") {
+            *children = self.expand_parts(std::mem::take(children), depth)?;
+        }
+        if let Some(child) = part.get_mut("child") {
+            *child = self.expand(child.take(), depth)?;
+        }
+        Ok(Value::Object(part))
+    }
+
+    /// The part an instance of the component `name` stands for.
+    fn instantiate(
+        &self,
+        mut instance: Map<String, Value>,
+        name: &Value,
+        depth: usize,
+    ) -> Result<Map<String, Value>, SkinError> {
+        let id = instance
+            .get("id")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let error = |msg: String| SkinError::InvalidComponent(format!("'{}' {}", id, msg));
+
+        let template = name
+            .as_str()
+            .and_then(|name| self.0.get(name))
+            .ok_or_else(|| error(format!("uses unknown component {}", name)))?;
+        if depth >= MAX_DEPTH {
+            return Err(error(format!(
+                "nests components more than {} deep",
+                MAX_DEPTH
+            )));
+        }
+
+        let mut args = match template.get("params") {
+            Some(Value::Object(params)) => params.clone(),
+            _ => Map::new(),
+        };
+        match instance.remove("args") {
+            Some(Value::Object(given)) => args.extend(given),
+            Some(_) => return Err(error("has 'args' that aren't an object".to_string())),
+            None => {}
+        }
+        args.insert("id".to_string(), Value::String(id.clone()));
+
+        // Components without a type are groups of their parts
+        let mut part = Map::new();
+        part.insert("type".to_string(), Value::String("group".to_string()));
+        for (key, value) in template {
+            if key != "params" {
+                part.insert(key.clone(), substitute(value.clone(), &args, &id)?);
+            }
+        }
+        if let Some(Value::Array(children)) = part.get_mut("parts") {
+            for child in children {
+                prefix_ids(child, &id);
+            }
+        }
+        part.extend(instance);
+        Ok(part)
+    }
+}
+
+/// Fill in the `${name}` placeholders in every string of `value`.
+fn substitute(value: Value, args: &Map<String, Value>, owner: &str) -> Result<Value, SkinError> {
+    let arg = |name: &str| {
+        args.get(name).ok_or_else(|| {
+            SkinError::InvalidComponent(format!("'{}' has no value for '${{{}}}'", owner, name))
+        })
+    };
+
+    match value {
+        Value::String(text) => {
+            // A lone placeholder keeps the argument's type
+            if let Some(name) = text
+                .strip_prefix("${")
+                .and_then(|rest| rest.strip_suffix('}'))
+                && !name.contains('}')
+            {
+                return arg(name).cloned();
+            }
+
+            let mut filled = String::new();
+            let mut rest = text.as_str();
+            while let Some(start) = rest.find("${")
+                && let Some(length) = rest[start + 2..].find('}')
+            {
+                filled.push_str(&rest[..start]);
+                match arg(&rest[start + 2..start + 2 + length])? {
+                    Value::String(value) => filled.push_str(value),
+                    Value::Null => {}
+                    other => filled.push_str(&other.to_string()),
+                }
+                rest = &rest[start + 3 + length..];
+            }
+            filled.push_str(rest);
+            Ok(Value::String(filled))
+        }
+        Value::Array(items) => items
+            .into_iter()
+            .map(|item| substitute(item, args, owner))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        Value::Object(fields) => fields
+            .into_iter()
+            .map(|(key, value)| Ok((key, substitute(value, args, owner)?)))
+            .collect::<Result<_, _>>()
+            .map(Value::Object),
+        other => Ok(other),
+    }
+}
+
+/// Put `prefix.` in front of the ids of a part and the parts inside it.
+fn prefix_ids(part: &mut Value, prefix: &str) {
+    let Value::Object(part) = part else {
+        return;
+    };
+    if let Some(Value::String(id)) = part.get_mut("id") {
+        *id = format!("{}.{}", prefix, id);
+    }
+    if let Some(Value::Array(children)) = part.get_mut("parts") {
+        for child in children {
+            prefix_ids(child, prefix);
+        }
+    }
+    if let Some(child) = part.get_mut("child") {
+        prefix_ids(child, prefix);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn components(value: Value) -> Components {
+        serde_json::from_value(value).map(Components).unwrap()
+    }
+
+    #[test]
+    fn test_instances_expand_with_arguments_and_unique_ids() {
+        let components = components(json!({
+            "field": {
+                "type": "stack",
+                "params": { "max_length": 3, "label_binding": null },
+                "parts": [
+                    { "id": "label", "type": "static_text", "content": "${label} (${max_length})",
+                      "binding": "${label_binding}" },
+                    { "id": "input", "type": "text_input", "binding": "inputs.${id}",
+                      "max_length": "${max_length}" }
+                ]
+            }
+        }));
+        let parts = vec![json!({
+            "id": "fuel", "component": "field", "x": 20, "args": { "label": "Fuel" }
+        })];
+
+        let expanded = components.expand_all(parts).unwrap();
+        assert_eq!(
+            expanded,
+            [json!({
+                "id": "fuel", "type": "stack", "x": 20,
+                "parts": [
+                    { "id": "fuel.label", "type": "static_text", "content": "Fuel (3)",
+                      "binding": null },
+                    { "id": "fuel.input", "type": "text_input", "binding": "inputs.fuel",
+                      "max_length": 3 }
+                ]
+            })]
+        );
+    }
+
+    #[test]
+    fn test_missing_arguments_and_cycles_are_errors() {
+        let components = components(json!({
+            "label": { "type": "static_text", "content": "${text}" },
+            "loop": { "parts": [{ "id": "again", "component": "loop" }] }
+        }));
+
+        let missing = components.expand_all(vec![json!({ "id": "title", "component": "label" })]);
+        let missing = missing.map(|_| ()).unwrap_err().to_string();
+        assert!(
+            missing.contains("'title' has no value for '${text}'"),
+            "{}",
+            missing
+        );
+
+        let cycle = components.expand_all(vec![json!({ "id": "a", "component": "loop" })]);
+        assert!(matches!(cycle, Err(SkinError::InvalidComponent(_))));
+
+        let unknown = components.expand_all(vec![json!({ "id": "b", "component": "nope" })]);
+        assert!(matches!(unknown, Err(SkinError::InvalidComponent(_))));
+    }
+}
@@ -2,20 +2,99 @@ use std::collections::HashMap;
 use std::path::Path;
 
 use serde::Deserialize;
+use serde_json::{Map, Value};
 
+use crate::core::{Align, Anchor, Axis, Grid, Justify, Layout, Stack};
+use crate::graphics::{
+    NineSlice, Playback, SliceMode, TextEffects, TextGlow, TextOutline, TextShadow,
+};
+
+use super::components::Components;
 use super::types::{
     CheckboxDraw, DirectoryPickerDraw, FilePickerDraw, HitType, PartDraw, PartHit, PartType,
-    ScrollbarDraw, Skin, SkinError, SkinMeta, SkinPart, SkinWindow, TextAlign, TextInputDraw,
-    TextValidation, VerticalAlign,
+    ScrollbarDraw, Skin, SkinAsset, SkinError, SkinFont, SkinMeta, SkinPart, SkinWindow, TextAlign,
+    TextInputDraw, TextValidation, TextWrap, VerticalAlign,
 };
 
 #[derive(Deserialize)]
 struct SkinJson {
     skin: SkinMetaJson,
     window: SkinWindowJson,
-    assets: HashMap<String, String>,
+    assets: HashMap<String, AssetJson>,
+    /// Color made transparent in every asset that doesn't set its own.
     #[serde(default)]
-    parts: Vec<SkinPartJson>,
+    color_key: Option<String>,
+    /// Fonts by key, with paths relative to the skin directory.
+    #[serde(default)]
+    fonts: HashMap<String, FontJson>,
+    /// Font keys tried, in order, for characters a part's font lacks.
+    #[serde(default)]
+    font_fallback: Vec<String>,
+    /// Part templates by name, expanded wherever a part uses one.
+    #[serde(default)]
+    components: HashMap<String, Map<String, Value>>,
+    /// Parts, kept as raw JSON until their components are expanded.
+    #[serde(default)]
+    parts: Vec<Value>,
+}
+
+/// An asset entry: either a bare image path or an object with extra options.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AssetJson {
+    Path(String),
+    Detailed(AssetDetailJson),
+}
+
+/// An asset object. Exactly one of `image` or `sheet` is set; sheets
+/// require a `rect` selecting the sub-image.
+#[derive(Deserialize)]
+struct AssetDetailJson {
+    #[serde(default)]
+    image: Option<String>,
+    #[serde(default)]
+    sheet: Option<String>,
+    #[serde(default)]
+    rect: Option<[u32; 4]>,
+    #[serde(default)]
+    nine_slice: Option<NineSliceJson>,
+    #[serde(default)]
+    frames: Option<u32>,
+    #[serde(default)]
+    frame_duration: Option<u32>,
+    #[serde(default)]
+    play: Option<String>,
+    /// Overrides the skin's `color_key`; "none" keeps every color opaque.
+    #[serde(default)]
+    color_key: Option<String>,
+}
+
+/// A font entry: a TTF/OTF or BMFont `.fnt` path, or a character grid sheet.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FontJson {
+    Path(String),
+    Grid(GridFontJson),
+}
+
+#[derive(Deserialize)]
+struct GridFontJson {
+    sheet: String,
+    /// Cell width and height in pixels.
+    cell: [u32; 2],
+    chars: String,
+}
+
+#[derive(Deserialize)]
+struct NineSliceJson {
+    left: u32,
+    top: u32,
+    right: u32,
+    bottom: u32,
+    #[serde(default)]
+    edges: Option<String>,
+    #[serde(default)]
+    center: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -31,6 +110,18 @@ struct SkinWindowJson {
     height: u32,
     #[serde(default)]
     resizable: bool,
+    #[serde(default = "default_decorations")]
+    decorations: bool,
+    #[serde(default)]
+    shape: Option<String>,
+    #[serde(default)]
+    min_size: Option<[u32; 2]>,
+    #[serde(default)]
+    max_size: Option<[u32; 2]>,
+}
+
+fn default_decorations() -> bool {
+    true
 }
 
 #[derive(Deserialize)]
@@ -40,9 +131,15 @@ struct SkinPartJson {
     part_type: String,
     #[serde(default)]
     asset: Option<String>,
+    /// Position, ignored inside layouts.
+    #[serde(default)]
     x: i32,
+    #[serde(default)]
     y: i32,
+    /// Size; 0 or missing uses the part's natural size.
+    #[serde(default)]
     width: u32,
+    #[serde(default)]
     height: u32,
     #[serde(default)]
     z: i32,
@@ -65,8 +162,16 @@ struct SkinPartJson {
     #[serde(default)]
     text_color: Option<String>,
     #[serde(default)]
+    text_shadow: Option<TextShadowJson>,
+    #[serde(default)]
+    text_outline: Option<TextOutlineJson>,
+    #[serde(default)]
+    text_glow: Option<TextGlowJson>,
+    #[serde(default)]
     padding: Option<u32>,
     #[serde(default)]
+    font: Option<String>,
+    #[serde(default)]
     font_size: Option<f32>,
     #[serde(default)]
     max_length: Option<u32>,
@@ -81,6 +186,12 @@ struct SkinPartJson {
     #[serde(default)]
     vertical_align: Option<String>,
     #[serde(default)]
+    wrap: Option<String>,
+    #[serde(default)]
+    line_spacing: Option<f32>,
+    #[serde(default)]
+    max_lines: Option<u32>,
+    #[serde(default)]
     binding: Option<String>,
     #[serde(default)]
     content_height: Option<u32>,
@@ -90,6 +201,48 @@ struct SkinPartJson {
     filter: Option<String>,
     #[serde(default)]
     on_select: Option<String>,
+    /// Parts inside a group, image, stack or grid.
+    #[serde(default)]
+    parts: Vec<SkinPartJson>,
+    #[serde(default)]
+    direction: Option<String>,
+    #[serde(default)]
+    spacing: Option<u32>,
+    #[serde(default)]
+    row_spacing: Option<u32>,
+    #[serde(default)]
+    align: Option<String>,
+    #[serde(default)]
+    justify: Option<String>,
+    #[serde(default)]
+    columns: Option<ColumnsJson>,
+    #[serde(default)]
+    anchor: Option<AnchorJson>,
+    #[serde(default = "default_visible")]
+    visible: bool,
+    #[serde(default)]
+    clip: bool,
+}
+
+fn default_visible() -> bool {
+    true
+}
+
+/// The edges a part is anchored to: one name or a list of them.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AnchorJson {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Grid columns: a count of equal columns, or a width for each (0 to share
+/// the leftover space).
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColumnsJson {
+    Count(u32),
+    Widths(Vec<u32>),
 }
 
 #[derive(Deserialize)]
@@ -142,10 +295,55 @@ struct CheckboxDrawJson {
     checked: String,
 }
 
+/// Colors in text effects are hex strings like `text_color`.
+#[derive(Deserialize)]
+struct TextShadowJson {
+    #[serde(default = "default_shadow_offset")]
+    offset: [i32; 2],
+    #[serde(default)]
+    color: Option<String>,
+    #[serde(default)]
+    blur: u32,
+}
+
+fn default_shadow_offset() -> [i32; 2] {
+    [1, 1]
+}
+
+#[derive(Deserialize)]
+struct TextOutlineJson {
+    #[serde(default = "default_effect_size")]
+    width: u32,
+    #[serde(default)]
+    color: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TextGlowJson {
+    #[serde(default = "default_glow_radius")]
+    radius: u32,
+    #[serde(default)]
+    color: Option<String>,
+}
+
+fn default_effect_size() -> u32 {
+    1
+}
+
+fn default_glow_radius() -> u32 {
+    4
+}
+
 #[derive(Deserialize)]
 struct PartHitJson {
     #[serde(rename = "type")]
     hit_type: String,
+    /// Polygon points, relative to the part's top-left corner.
+    #[serde(default)]
+    points: Option<Vec<[i32; 2]>>,
+    /// Image for alpha and mask hit testing.
+    #[serde(default)]
+    asset: Option<String>,
 }
 
 impl Skin {
@@ -155,6 +353,10 @@ impl Skin {
         let json: SkinJson = serde_json::from_str(&content)?;
 
         let base_path = path.parent().unwrap_or(Path::new("."));
+        let color_key = match &json.color_key {
+            Some(value) => Self::parse_color_key("skin", value)?,
+            None => None,
+        };
 
         Ok(Skin {
             meta: SkinMeta {
@@ -166,20 +368,197 @@ impl Skin {
                 width: json.window.width,
                 height: json.window.height,
                 resizable: json.window.resizable,
+                decorations: json.window.decorations,
+                shape: json.window.shape,
+                min_size: json.window.min_size.map(|[w, h]| (w, h)),
+                max_size: json.window.max_size.map(|[w, h]| (w, h)),
             },
             assets: json
                 .assets
                 .into_iter()
-                .map(|(k, v)| (k, base_path.join(v)))
+                .map(|(k, v)| {
+                    let asset = Self::convert_asset(&k, v, base_path, color_key)?;
+                    Ok((k, asset))
+                })
+                .collect::<Result<HashMap<_, _>, SkinError>>()?,
+            fonts: json
+                .fonts
+                .into_iter()
+                .map(|(k, v)| (k, Self::convert_font(v, base_path)))
                 .collect(),
-            parts: json
-                .parts
+            font_fallback: json.font_fallback,
+            parts: Components(json.components)
+                .expand_all(json.parts)?
                 .into_iter()
-                .map(|p| Self::convert_part(p))
+                .map(|part| Self::convert_part(serde_json::from_value(part)?))
                 .collect::<Result<Vec<_>, _>>()?,
         })
     }
 
+    fn convert_hit(id: &str, hit: PartHitJson) -> Result<HitType, SkinError> {
+        match hit.hit_type.as_str() {
+            "rect" => Ok(HitType::Rect),
+            "alpha" => Ok(HitType::Alpha { asset: hit.asset }),
+            "ellipse" => Ok(HitType::Ellipse),
+            "polygon" => match hit.points {
+                Some(points) if points.len() >= 3 => Ok(HitType::Polygon(
+                    points.into_iter().map(|[x, y]| (x, y)).collect(),
+                )),
+                _ => Err(SkinError::InvalidHit(format!(
+                    "'{}' needs at least 3 polygon 'points'",
+                    id
+                ))),
+            },
+            "mask" => hit
+                .asset
+                .map(|asset| HitType::Mask { asset })
+                .ok_or_else(|| SkinError::InvalidHit(format!("'{}' mask needs an 'asset'", id))),
+            other => Err(SkinError::InvalidHit(format!(
+                "'{}' has unknown hit type '{}'",
+                id, other
+            ))),
+        }
+    }
+
+    fn convert_font(font: FontJson, base_path: &Path) -> SkinFont {
+        match font {
+            FontJson::Path(path) => SkinFont::File(base_path.join(path)),
+            FontJson::Grid(grid) => SkinFont::Grid {
+                sheet: base_path.join(grid.sheet),
+                cell_width: grid.cell[0],
+                cell_height: grid.cell[1],
+                chars: grid.chars,
+            },
+        }
+    }
+
+    /// Convert an asset entry. `color_key` is the skin-wide key, used unless
+    /// the asset sets its own.
+    fn convert_asset(
+        key: &str,
+        asset: AssetJson,
+        base_path: &Path,
+        color_key: Option<u32>,
+    ) -> Result<SkinAsset, SkinError> {
+        let detail = match asset {
+            AssetJson::Path(path) => {
+                return Ok(SkinAsset {
+                    path: base_path.join(path),
+                    rect: None,
+                    nine_slice: None,
+                    frames: None,
+                    frame_duration: None,
+                    playback: Playback::Loop,
+                    color_key,
+                });
+            }
+            AssetJson::Detailed(detail) => detail,
+        };
+
+        let path = match (detail.image, detail.sheet) {
+            (Some(image), None) => image,
+            (None, Some(sheet)) => {
+                if detail.rect.is_none() {
+                    return Err(SkinError::InvalidAsset(format!(
+                        "'{}' uses a sheet but has no 'rect'",
+                        key
+                    )));
+                }
+                sheet
+            }
+            (Some(_), Some(_)) => {
+                return Err(SkinError::InvalidAsset(format!(
+                    "'{}' sets both 'image' and 'sheet'",
+                    key
+                )));
+            }
+            (None, None) => {
+                return Err(SkinError::InvalidAsset(format!(
+                    "'{}' needs an 'image' or 'sheet'",
+                    key
+                )));
+            }
+        };
+
+        if let Some([_, _, width, height]) = detail.rect
+            && (width == 0 || height == 0)
+        {
+            return Err(SkinError::InvalidAsset(format!(
+                "'{}' has an empty rect",
+                key
+            )));
+        }
+
+        if detail.frames == Some(0) || detail.frame_duration == Some(0) {
+            return Err(SkinError::InvalidAsset(format!(
+                "'{}' needs at least one frame with a non-zero duration",
+                key
+            )));
+        }
+
+        let playback = match detail.play.as_deref() {
+            None | Some("loop") => Playback::Loop,
+            Some("once") => Playback::Once,
+            Some(other) => {
+                return Err(SkinError::InvalidAsset(format!(
+                    "'{}' has unknown play mode '{}'",
+                    key, other
+                )));
+            }
+        };
+
+        let nine_slice = match detail.nine_slice {
+            Some(n) => Some(NineSlice {
+                left: n.left,
+                top: n.top,
+                right: n.right,
+                bottom: n.bottom,
+                edges: Self::parse_slice_mode(key, n.edges.as_deref())?,
+                center: Self::parse_slice_mode(key, n.center.as_deref())?,
+            }),
+            None => None,
+        };
+
+        let color_key = match &detail.color_key {
+            Some(value) => Self::parse_color_key(key, value)?,
+            None => color_key,
+        };
+
+        Ok(SkinAsset {
+            path: base_path.join(path),
+            rect: detail.rect,
+            nine_slice,
+            frames: detail.frames,
+            frame_duration: detail.frame_duration,
+            playback,
+            color_key,
+        })
+    }
+
+    /// Parse a `color_key` value: a hex RGB color, or "none" for no key.
+    fn parse_color_key(owner: &str, value: &str) -> Result<Option<u32>, SkinError> {
+        if value == "none" {
+            return Ok(None);
+        }
+        parse_color(value)
+            .filter(|&color| color <= 0xFFFFFF)
+            .map(Some)
+            .ok_or_else(|| {
+                SkinError::InvalidAsset(format!("'{}' has invalid color_key '{}'", owner, value))
+            })
+    }
+
+    fn parse_slice_mode(key: &str, mode: Option<&str>) -> Result<SliceMode, SkinError> {
+        match mode {
+            None | Some("stretch") => Ok(SliceMode::Stretch),
+            Some("tile") => Ok(SliceMode::Tile),
+            Some(other) => Err(SkinError::InvalidAsset(format!(
+                "'{}' has unknown slice mode '{}'",
+                key, other
+            ))),
+        }
+    }
+
     fn convert_part(p: SkinPartJson) -> Result<SkinPart, SkinError> {
         let part_type = match p.part_type.as_str() {
             "image" => {
@@ -195,8 +574,21 @@ impl Skin {
             "directory_picker" => PartType::DirectoryPicker,
             "file_picker" => PartType::FilePicker,
             "checkbox" => PartType::Checkbox,
+            "drag_region" => PartType::DragRegion,
+            "stack" | "grid" => PartType::Layout(Self::convert_layout(&p)),
+            "group" => PartType::Group,
             other => return Err(SkinError::InvalidPartType(other.to_string())),
         };
+        let container = matches!(
+            part_type,
+            PartType::Layout(_) | PartType::Group | PartType::Image { .. }
+        );
+        if !p.parts.is_empty() && !container {
+            return Err(SkinError::InvalidPartType(format!(
+                "'{}' is a {} and can't contain parts",
+                p.id, p.part_type
+            )));
+        }
 
         let draw = p.draw.map(|d| PartDraw {
             normal: d.normal,
@@ -241,17 +633,35 @@ impl Skin {
             thumb: s.thumb,
         });
 
-        let hit = p.hit.map(|h| PartHit {
-            hit_type: match h.hit_type.as_str() {
-                "rect" | _ => HitType::Rect,
-            },
-        });
+        let hit = match p.hit {
+            Some(h) => Some(PartHit {
+                hit_type: Self::convert_hit(&p.id, h)?,
+            }),
+            None => None,
+        };
 
         // Parse text_color from hex string like "0x000000"
-        let text_color = p.text_color.and_then(|s| {
-            let s = s.trim_start_matches("0x").trim_start_matches("0X");
-            u32::from_str_radix(s, 16).ok()
-        });
+        let text_color = p.text_color.as_deref().and_then(parse_color);
+
+        // Shadows and outlines default to black, glows to white
+        let effect_color = |color: Option<String>, default| {
+            color.as_deref().and_then(parse_color).unwrap_or(default)
+        };
+        let text_effects = TextEffects {
+            shadow: p.text_shadow.map(|s| TextShadow {
+                offset: (s.offset[0], s.offset[1]),
+                color: effect_color(s.color, 0x000000),
+                blur: s.blur,
+            }),
+            outline: p.text_outline.map(|o| TextOutline {
+                width: o.width,
+                color: effect_color(o.color, 0x000000),
+            }),
+            glow: p.text_glow.map(|g| TextGlow {
+                radius: g.radius,
+                color: effect_color(g.color, 0xFFFFFF),
+            }),
+        };
 
         // Parse validation mode
         let validation = p.validation.map(|s| match s.as_str() {
@@ -278,11 +688,27 @@ impl Skin {
             _ => VerticalAlign::Center,
         });
 
+        // Parse line wrapping
+        let wrap = p.wrap.map(|s| match s.as_str() {
+            "word" => TextWrap::Word,
+            "char" => TextWrap::Char,
+            _ => TextWrap::None,
+        });
+
         // Parse child recursively
         let child = match p.child {
             Some(child_json) => Some(Box::new(Self::convert_part(*child_json)?)),
             None => None,
         };
+        let children = p
+            .parts
+            .into_iter()
+            .map(Self::convert_part)
+            .collect::<Result<Vec<_>, _>>()?;
+        let anchor = match p.anchor {
+            Some(anchor) => Some(Self::convert_anchor(&p.id, anchor)?),
+            None => None,
+        };
 
         Ok(SkinPart {
             id: p.id,
@@ -301,7 +727,9 @@ impl Skin {
             hit,
             action: p.action,
             text_color,
+            text_effects,
             padding: p.padding,
+            font: p.font,
             font_size: p.font_size,
             max_length: p.max_length,
             validation,
@@ -309,11 +737,190 @@ impl Skin {
             label: p.label,
             text_align,
             vertical_align,
+            wrap,
+            line_spacing: p.line_spacing,
+            max_lines: p.max_lines,
             binding: p.binding,
             content_height: p.content_height,
             child,
             filter: p.filter,
             on_select: p.on_select,
+            children,
+            anchor,
+            visible: p.visible,
+            clip: p.clip,
         })
     }
+
+    /// Turn edge names into an anchor. An axis anchored to both of its
+    /// edges stretches; one with neither stays at its start unless `center`
+    /// is given.
+    fn convert_anchor(id: &str, anchor: AnchorJson) -> Result<Anchor, SkinError> {
+        let names = match anchor {
+            AnchorJson::One(name) => vec![name],
+            AnchorJson::Many(names) => names,
+        };
+        let (mut left, mut right, mut top, mut bottom) = (false, false, false, false);
+        let mut center = false;
+        for name in &names {
+            match name.as_str() {
+                "left" => left = true,
+                "right" => right = true,
+                "top" => top = true,
+                "bottom" => bottom = true,
+                "center" => center = true,
+                "stretch" => (left, right, top, bottom) = (true, true, true, true),
+                other => {
+                    return Err(SkinError::InvalidAnchor(format!(
+                        "'{}' has unknown edge '{}' (expected left, right, top, bottom, \
+                         center or stretch)",
+                        id, other
+                    )));
+                }
+            }
+        }
+        let axis = |start, end| match (start, end) {
+            (true, true) => Align::Stretch,
+            (false, true) => Align::End,
+            (false, false) if center => Align::Center,
+            _ => Align::Start,
+        };
+        Ok(Anchor {
+            x: axis(left, right),
+            y: axis(top, bottom),
+        })
+    }
+
+    /// The layout of a `stack` or `grid` part.
+    fn convert_layout(p: &SkinPartJson) -> Layout {
+        let align = match p.align.as_deref() {
+            Some("center") => Align::Center,
+            Some("end") => Align::End,
+            Some("stretch") => Align::Stretch,
+            _ => Align::Start,
+        };
+        let spacing = p.spacing.unwrap_or(0);
+        let padding = p.padding.unwrap_or(0);
+
+        if p.part_type == "grid" {
+            let columns = match &p.columns {
+                Some(ColumnsJson::Count(count)) => vec![0; (*count).max(1) as usize],
+                Some(ColumnsJson::Widths(widths)) if !widths.is_empty() => widths.clone(),
+                _ => vec![0],
+            };
+            return Layout::Grid(Grid {
+                columns,
+                column_spacing: spacing,
+                row_spacing: p.row_spacing.unwrap_or(spacing),
+                align,
+                padding,
+            });
+        }
+
+        Layout::Stack(Stack {
+            axis: match p.direction.as_deref() {
+                Some("horizontal") => Axis::Horizontal,
+                _ => Axis::Vertical,
+            },
+            spacing,
+            align,
+            justify: match p.justify.as_deref() {
+                Some("center") => Justify::Center,
+                Some("end") => Justify::End,
+                Some("space_between") => Justify::SpaceBetween,
+                _ => Justify::Start,
+            },
+            padding,
+        })
+    }
+}
+
+/// Parse a hex color string like "0x000000" or "#000000".
+fn parse_color(s: &str) -> Option<u32> {
+    let s = s
+        .trim_start_matches('#')
+        .trim_start_matches("0x")
+        .trim_start_matches("0X");
+    u32::from_str_radix(s, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::skin::LoadedSkin;
+    use image::RgbaImage;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    /// Write a skin with `assets` and `parts` to its own temp directory and
+    /// return the path of its `skin.json`.
+    fn write_skin(name: &str, assets: Value, parts: Value) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("crix_loader_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let skin = json!({
+            "skin": { "name": name, "author": "test", "version": "1.0" },
+            "window": { "width": 40, "height": 20 },
+            "assets": assets,
+            "parts": parts,
+        });
+        let path = dir.join("skin.json");
+        std::fs::write(&path, skin.to_string()).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_unknown_hit_type_is_an_error() {
+        let part = json!({
+            "id": "knob", "type": "image", "asset": "knob", "hit": { "type": "star" }
+        });
+        let path = write_skin("hit", json!({ "knob": "knob.png" }), json!([part]));
+
+        let error = Skin::load(&path).map(|_| ()).unwrap_err();
+        assert!(matches!(error, SkinError::InvalidHit(_)));
+        assert!(
+            error
+                .to_string()
+                .contains("'knob' has unknown hit type 'star'"),
+            "{}",
+            error
+        );
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_zero_frames_is_an_error() {
+        let assets = json!({ "spinner": { "image": "spinner.png", "frames": 0 } });
+        let path = write_skin("frames", assets, json!([]));
+
+        let error = Skin::load(&path).map(|_| ()).unwrap_err();
+        assert!(matches!(error, SkinError::InvalidAsset(_)));
+        assert!(
+            error
+                .to_string()
+                .contains("'spinner' needs at least one frame"),
+            "{}",
+            error
+        );
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_frame_strip_must_split_evenly() {
+        let assets = json!({ "spinner": { "image": "spinner.png", "frames": 3 } });
+        let path = write_skin("strip", assets, json!([]));
+        let dir = path.parent().unwrap();
+        RgbaImage::new(10, 4).save(dir.join("spinner.png")).unwrap();
+
+        // The skin parses; the strip is checked once the image is decoded
+        assert_eq!(Skin::load(&path).unwrap().assets["spinner"].frames, Some(3));
+        let error = LoadedSkin::load(&path).map(|_| ()).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("doesn't split into 3 equal frames"),
+            "{}",
+            error
+        );
+        std::fs::remove_dir_all(dir).ok();
+    }
 }
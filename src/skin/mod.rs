@@ -1,10 +1,12 @@
 mod assets;
 mod builder;
+mod components;
 mod loader;
 mod types;
+mod wsz;
 pub mod widgets;
 
 pub use assets::LoadedSkin;
 pub use builder::SkinBuilder;
-pub use types::{SkinError, SkinWindow, TextAlign, VerticalAlign};
+pub use types::{Skin, SkinError, SkinFont, SkinWindow, TextAlign, TextWrap, VerticalAlign};
 pub use widgets::{SkinVScroll, StaticText, TextInput};
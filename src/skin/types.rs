@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+use crate::core::{Anchor, Layout};
+use crate::graphics::{FontError, NineSlice, Playback, TextEffects};
+
 /// Skin metadata from [skin] section.
 #[derive(Debug, Clone)]
 pub struct SkinMeta {
@@ -15,6 +18,33 @@ pub struct SkinWindow {
     pub width: u32,
     pub height: u32,
     pub resizable: bool,
+    /// Whether the OS draws a title bar and border (default true).
+    pub decorations: bool,
+    /// Asset whose opaque pixels give the window its shape.
+    pub shape: Option<String>,
+    /// Smallest (width, height) a resizable window can be resized to.
+    pub min_size: Option<(u32, u32)>,
+    /// Largest (width, height) a resizable window can be resized to.
+    pub max_size: Option<(u32, u32)>,
+}
+
+/// An image asset declared in the skin's assets section.
+#[derive(Debug, Clone)]
+pub struct SkinAsset {
+    /// Resolved path to the image file (or sprite sheet).
+    pub path: PathBuf,
+    /// Sub-rectangle `[x, y, width, height]` of a sprite sheet to use.
+    pub rect: Option<[u32; 4]>,
+    /// Optional nine-slice scaling definition.
+    pub nine_slice: Option<NineSlice>,
+    /// Split the image into this many equal frames laid out horizontally.
+    pub frames: Option<u32>,
+    /// Display time of each frame in milliseconds (overrides GIF/APNG delays).
+    pub frame_duration: Option<u32>,
+    /// How animated assets play.
+    pub playback: Playback,
+    /// Pixels of exactly this color (0xRRGGBB) are made transparent.
+    pub color_key: Option<u32>,
 }
 
 /// Drawing configuration for stateful widgets (buttons).
@@ -92,7 +122,16 @@ pub struct PartHit {
 /// Hit region type.
 #[derive(Debug, Clone)]
 pub enum HitType {
+    /// The whole part (default)
     Rect,
+    /// Only where the asset (the part's own image if not given) is non-transparent
+    Alpha { asset: Option<String> },
+    /// The ellipse inscribed in the part
+    Ellipse,
+    /// A polygon with points relative to the part's top-left corner
+    Polygon(Vec<(i32, i32)>),
+    /// Only where a separate mask asset is non-transparent
+    Mask { asset: String },
 }
 
 /// Horizontal text alignment.
@@ -113,6 +152,18 @@ pub enum VerticalAlign {
     Bottom,
 }
 
+/// How static text breaks lines that are wider than the part.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum TextWrap {
+    /// Only break at newlines (default)
+    #[default]
+    None,
+    /// Break between words, and inside words too long for a line
+    Word,
+    /// Break between any two characters
+    Char,
+}
+
 /// Part type discriminator.
 #[derive(Debug, Clone)]
 pub enum PartType {
@@ -124,6 +175,12 @@ pub enum PartType {
     DirectoryPicker,
     FilePicker,
     Checkbox,
+    /// An invisible area that moves the window when dragged.
+    DragRegion,
+    /// An invisible container that places its `children`.
+    Layout(Layout),
+    /// An invisible container for `children` positioned relative to it.
+    Group,
 }
 
 /// Validation mode for text input.
@@ -160,8 +217,12 @@ pub struct SkinPart {
     pub hit: Option<PartHit>,
     pub action: Option<String>,
     pub text_color: Option<u32>,
+    /// Shadow, outline and glow drawn behind the part's text
+    pub text_effects: TextEffects,
     pub padding: Option<u32>,
-    /// Font size in pixels (uses global font size if not specified)
+    /// Key into the skin's fonts (uses the default font if not specified)
+    pub font: Option<String>,
+    /// Font size in pixels (uses the bundle's font size if not specified)
     pub font_size: Option<f32>,
     /// Maximum number of characters allowed
     pub max_length: Option<u32>,
@@ -175,6 +236,12 @@ pub struct SkinPart {
     pub text_align: Option<TextAlign>,
     /// Vertical text alignment
     pub vertical_align: Option<VerticalAlign>,
+    /// Line wrapping for static text
+    pub wrap: Option<TextWrap>,
+    /// Distance between lines as a multiple of the line height
+    pub line_spacing: Option<f32>,
+    /// Lines shown before the text is cut off with an ellipsis
+    pub max_lines: Option<u32>,
     /// Store binding key for reading/writing values
     pub binding: Option<String>,
     /// Content height for scroll containers
@@ -185,6 +252,28 @@ pub struct SkinPart {
     pub filter: Option<String>,
     /// Action to trigger on file selection
     pub on_select: Option<String>,
+    /// Parts inside this one, placed relative to it or by its layout
+    pub children: Vec<SkinPart>,
+    /// Edges of the parent the part follows when the window is resized
+    pub anchor: Option<Anchor>,
+    /// Whether the part (and everything inside it) starts out shown
+    pub visible: bool,
+    /// Whether the parts inside are cut off at this part's bounds
+    pub clip: bool,
+}
+
+/// A font declared in the skin's fonts section.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SkinFont {
+    /// A TTF/OTF font, or a BMFont `.fnt` file with its page images.
+    File(PathBuf),
+    /// A sheet of equally sized character cells, assigned to `chars` row by row.
+    Grid {
+        sheet: PathBuf,
+        cell_width: u32,
+        cell_height: u32,
+        chars: String,
+    },
 }
 
 /// The root skin structure parsed from skin.toml.
@@ -192,7 +281,11 @@ pub struct SkinPart {
 pub struct Skin {
     pub meta: SkinMeta,
     pub window: SkinWindow,
-    pub assets: HashMap<String, PathBuf>,
+    pub assets: HashMap<String, SkinAsset>,
+    /// Fonts by key, with resolved paths.
+    pub fonts: HashMap<String, SkinFont>,
+    /// Font keys tried, in order, for characters a part's font lacks.
+    pub font_fallback: Vec<String>,
     pub parts: Vec<SkinPart>,
 }
 
@@ -204,7 +297,14 @@ pub enum SkinError {
     AssetNotFound(String),
     MissingDrawSection(String),
     InvalidPartType(String),
+    InvalidAsset(String),
     Image(image::ImageError),
+    Archive(zip::result::ZipError),
+    Font(FontError),
+    FontNotFound(String),
+    InvalidHit(String),
+    InvalidAnchor(String),
+    InvalidComponent(String),
 }
 
 impl std::fmt::Display for SkinError {
@@ -215,7 +315,14 @@ impl std::fmt::Display for SkinError {
             SkinError::AssetNotFound(key) => write!(f, "Asset not found: {}", key),
             SkinError::MissingDrawSection(id) => write!(f, "Missing 'draw' for button: {}", id),
             SkinError::InvalidPartType(t) => write!(f, "Invalid part type: {}", t),
+            SkinError::InvalidAsset(msg) => write!(f, "Invalid asset: {}", msg),
             SkinError::Image(e) => write!(f, "Image error: {}", e),
+            SkinError::Archive(e) => write!(f, "Archive error: {}", e),
+            SkinError::Font(e) => write!(f, "Font error: {}", e),
+            SkinError::FontNotFound(key) => write!(f, "Font not found: {}", key),
+            SkinError::InvalidHit(msg) => write!(f, "Invalid hit region: {}", msg),
+            SkinError::InvalidAnchor(msg) => write!(f, "Invalid anchor: {}", msg),
+            SkinError::InvalidComponent(msg) => write!(f, "Invalid component: {}", msg),
         }
     }
 }
@@ -239,3 +346,15 @@ impl From<image::ImageError> for SkinError {
         SkinError::Image(e)
     }
 }
+
+impl From<zip::result::ZipError> for SkinError {
+    fn from(e: zip::result::ZipError) -> Self {
+        SkinError::Archive(e)
+    }
+}
+
+impl From<FontError> for SkinError {
+    fn from(e: FontError) -> Self {
+        SkinError::Font(e)
+    }
+}
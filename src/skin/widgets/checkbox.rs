@@ -5,17 +5,18 @@
 
 use std::any::Any;
 
-use image::RgbImage;
-
 use crate::core::{Rect, Widget, WidgetEvent, WidgetState};
-use crate::graphics::{draw_text_sized, line_height_sized, Canvas, TextStyle};
+use crate::graphics::text::DEFAULT_FONT_SIZE;
+use crate::graphics::{
+    draw_text_sized, line_height_sized, Canvas, Font, Sprite, TextEffects, TextStyle,
+};
 
 /// A checkbox widget with two states: checked and unchecked.
 pub struct Checkbox {
     /// Image for unchecked state.
-    unchecked: RgbImage,
+    unchecked: Sprite,
     /// Image for checked state.
-    checked: RgbImage,
+    checked: Sprite,
     /// Widget dimensions.
     width: u32,
     height: u32,
@@ -25,6 +26,10 @@ pub struct Checkbox {
     label: Option<String>,
     /// Label text color.
     text_color: u32,
+    /// Shadow, outline and glow drawn behind the text.
+    text_effects: TextEffects,
+    /// Font for the label.
+    font: Font,
     /// Font size for label.
     font_size: Option<f32>,
     /// Padding between checkbox and label.
@@ -39,7 +44,7 @@ pub struct Checkbox {
 
 impl Checkbox {
     /// Create a new checkbox with the given state images.
-    pub fn new(unchecked: RgbImage, checked: RgbImage) -> Self {
+    pub fn new(unchecked: Sprite, checked: Sprite) -> Self {
         let width = unchecked.width();
         let height = unchecked.height();
         Self {
@@ -50,6 +55,8 @@ impl Checkbox {
             is_checked: false,
             label: None,
             text_color: 0xDDDDDD,
+            text_effects: TextEffects::default(),
+            font: Font::default(),
             font_size: None,
             padding: 8,
             binding: None,
@@ -70,6 +77,18 @@ impl Checkbox {
         self
     }
 
+    /// Set the shadow, outline and glow drawn behind the text.
+    pub fn with_text_effects(mut self, effects: TextEffects) -> Self {
+        self.text_effects = effects;
+        self
+    }
+
+    /// Set the label font.
+    pub fn with_font(mut self, font: Font) -> Self {
+        self.font = font;
+        self
+    }
+
     /// Set the font size.
     pub fn with_font_size(mut self, size: f32) -> Self {
         self.font_size = Some(size);
@@ -141,25 +160,7 @@ impl Checkbox {
 
     /// Get the effective font size.
     fn effective_font_size(&self) -> f32 {
-        self.font_size.unwrap_or(16.0)
-    }
-
-    fn draw_image(&self, canvas: &mut Canvas, x: i32, y: i32, image: &RgbImage, clip: Option<&Rect>) {
-        for (ix, iy, pixel) in image.enumerate_pixels() {
-            let px = x + ix as i32;
-            let py = y + iy as i32;
-
-            if let Some(clip) = clip {
-                if px < clip.x || px >= clip.right() || py < clip.y || py >= clip.bottom() {
-                    continue;
-                }
-            }
-
-            if px >= 0 && py >= 0 {
-                let [r, g, b] = pixel.0;
-                canvas.set_pixel_rgb(px as u32, py as u32, r, g, b);
-            }
-        }
+        self.font_size.unwrap_or(DEFAULT_FONT_SIZE)
     }
 }
 
@@ -172,25 +173,42 @@ impl Widget for Checkbox {
             &self.unchecked
         };
 
+        // Nine-slice boxes are square and as tall as the part; plain images
+        // keep their native size
+        let (box_width, box_height) = if image.is_scalable() {
+            (bounds.height, bounds.height)
+        } else {
+            (image.width(), image.height())
+        };
+
         // Center the checkbox image vertically
-        let img_height = image.height();
-        let y_offset = (bounds.height.saturating_sub(img_height)) / 2;
+        let y_offset = (bounds.height.saturating_sub(box_height)) / 2;
         let img_y = bounds.y + y_offset as i32;
 
         // Draw the checkbox image
-        self.draw_image(canvas, bounds.x, img_y, image, Some(bounds));
+        let dest = Rect::new(bounds.x, img_y, box_width, box_height);
+        image.draw_in(canvas, &dest, Some(bounds));
 
         // Draw label if present
         if let Some(ref label) = self.label {
             let font_size = self.effective_font_size();
-            let text_height = line_height_sized(font_size);
+            let text_height = line_height_sized(&self.font, font_size);
 
             // Position label to the right of the checkbox
-            let label_x = bounds.x + image.width() as i32 + self.padding as i32;
+            let label_x = bounds.x + box_width as i32 + self.padding as i32;
             let label_y = bounds.y + (bounds.height as i32 - text_height as i32) / 2;
 
-            let style = TextStyle::with_color(self.text_color);
-            draw_text_sized(canvas, label_x, label_y, Some(bounds), label, style, font_size);
+            let style = TextStyle::with_color(self.text_color).with_effects(self.text_effects);
+            draw_text_sized(
+                canvas,
+                &self.font,
+                label_x,
+                label_y,
+                Some(bounds),
+                label,
+                style,
+                font_size,
+            );
         }
     }
 
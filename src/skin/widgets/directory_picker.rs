@@ -6,21 +6,22 @@
 use std::any::Any;
 use std::path::PathBuf;
 
-use image::RgbImage;
-
 use crate::core::{Rect, Widget, WidgetEvent, WidgetState};
-use crate::graphics::{draw_text, Canvas, TextStyle};
+use crate::graphics::text::DEFAULT_FONT_SIZE;
+use crate::graphics::{draw_text_sized, Canvas, Font, Sprite, TextEffects, TextStyle};
+
+use super::elide_start;
 
 /// A directory picker widget with skinnable background and button.
 pub struct DirectoryPicker {
     /// Background image for normal state.
-    normal: RgbImage,
+    normal: Sprite,
     /// Background image for hover state.
-    hover: RgbImage,
+    hover: Sprite,
     /// Button image (the "..." button on the right).
-    button_normal: RgbImage,
+    button_normal: Sprite,
     /// Button image for hover state.
-    button_hover: RgbImage,
+    button_hover: Sprite,
     /// Widget dimensions.
     width: u32,
     height: u32,
@@ -30,10 +31,14 @@ pub struct DirectoryPicker {
     placeholder: String,
     /// Text color for the path display.
     text_color: u32,
+    /// Shadow, outline and glow drawn behind the text.
+    text_effects: TextEffects,
     /// Text color for placeholder.
     placeholder_color: u32,
     /// Padding from edges.
     padding: u32,
+    /// Font for the path display.
+    font: Font,
     /// Font size.
     font_size: Option<f32>,
     /// Store binding key.
@@ -50,12 +55,7 @@ pub struct DirectoryPicker {
 
 impl DirectoryPicker {
     /// Create a new directory picker.
-    pub fn new(
-        normal: RgbImage,
-        hover: RgbImage,
-        button_normal: RgbImage,
-        button_hover: RgbImage,
-    ) -> Self {
+    pub fn new(normal: Sprite, hover: Sprite, button_normal: Sprite, button_hover: Sprite) -> Self {
         let width = normal.width();
         let height = normal.height();
         let button_width = button_normal.width();
@@ -70,8 +70,10 @@ impl DirectoryPicker {
             selected_path: None,
             placeholder: "Select directory...".to_string(),
             text_color: 0x000000,
+            text_effects: TextEffects::default(),
             placeholder_color: 0x888888,
             padding: 8,
+            font: Font::default(),
             font_size: None,
             binding: None,
             dirty: false,
@@ -93,6 +95,12 @@ impl DirectoryPicker {
         self
     }
 
+    /// Set the shadow, outline and glow drawn behind the text.
+    pub fn with_text_effects(mut self, effects: TextEffects) -> Self {
+        self.text_effects = effects;
+        self
+    }
+
     /// Set placeholder color.
     pub fn with_placeholder_color(mut self, color: u32) -> Self {
         self.placeholder_color = color;
@@ -105,6 +113,12 @@ impl DirectoryPicker {
         self
     }
 
+    /// Set font.
+    pub fn with_font(mut self, font: Font) -> Self {
+        self.font = font;
+        self
+    }
+
     /// Set font size.
     pub fn with_font_size(mut self, size: f32) -> Self {
         self.font_size = Some(size);
@@ -176,18 +190,9 @@ impl DirectoryPicker {
         }
     }
 
-    /// Draw an image at a position.
-    fn draw_image(&self, canvas: &mut Canvas, image: &RgbImage, x: i32, y: i32, clip: &Rect) {
-        for (ix, iy, pixel) in image.enumerate_pixels() {
-            let px = x + ix as i32;
-            let py = y + iy as i32;
-            if px >= clip.x && px < clip.right() && py >= clip.y && py < clip.bottom() {
-                if px >= 0 && py >= 0 {
-                    let [r, g, b] = pixel.0;
-                    canvas.set_pixel_rgb(px as u32, py as u32, r, g, b);
-                }
-            }
-        }
+    /// Draw an image into a destination rect (scaling it if it is nine-sliced).
+    fn draw_image(&self, canvas: &mut Canvas, image: &Sprite, dest: &Rect) {
+        image.draw_in(canvas, dest, None);
     }
 }
 
@@ -199,29 +204,27 @@ impl Widget for DirectoryPicker {
         } else {
             &self.normal
         };
-        self.draw_image(canvas, bg, bounds.x, bounds.y, bounds);
+        self.draw_image(canvas, bg, bounds);
 
         // Draw button
-        let button_x = bounds.x + (self.width - self.button_width) as i32;
+        let button_x = bounds.x + bounds.width.saturating_sub(self.button_width) as i32;
         let button_img = if self.button_hovered {
             &self.button_hover
         } else {
             &self.button_normal
         };
-        self.draw_image(canvas, button_img, button_x, bounds.y, bounds);
+        let button_rect = Rect::new(button_x, bounds.y, self.button_width, bounds.height);
+        self.draw_image(canvas, button_img, &button_rect);
 
         // Draw text (path or placeholder)
         let text_x = bounds.x + self.padding as i32;
-        let text_y = bounds.y + (self.height / 2) as i32;
-        let text_width = self.width - self.button_width - self.padding * 2;
+        let text_y = bounds.y + (bounds.height / 2) as i32;
+        let text_width = bounds
+            .width
+            .saturating_sub(self.button_width + self.padding * 2);
 
         // Clip text to available area
-        let text_clip = Rect::new(
-            text_x,
-            bounds.y,
-            text_width,
-            self.height,
-        );
+        let text_clip = Rect::new(text_x, bounds.y, text_width, bounds.height);
 
         let (text, color) = if let Some(ref path) = self.selected_path {
             (path.to_string_lossy().to_string(), self.text_color)
@@ -230,17 +233,21 @@ impl Widget for DirectoryPicker {
         };
 
         // Truncate text if too long (simple truncation with ellipsis)
-        let display_text = if text.len() > 40 {
-            format!("...{}", &text[text.len() - 37..])
-        } else {
-            text
-        };
+        let display_text = elide_start(text, 40);
 
-        let style = TextStyle {
-            color,
-        };
+        let style = TextStyle::with_color(color).with_effects(self.text_effects);
 
-        draw_text(canvas, text_x, text_y, Some(&text_clip), &display_text, style);
+        let font_size = self.font_size.unwrap_or(DEFAULT_FONT_SIZE);
+        draw_text_sized(
+            canvas,
+            &self.font,
+            text_x,
+            text_y,
+            Some(&text_clip),
+            &display_text,
+            style,
+            font_size,
+        );
     }
 
     fn preferred_size(&self) -> (u32, u32) {
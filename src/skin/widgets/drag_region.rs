@@ -0,0 +1,27 @@
+use std::any::Any;
+
+use crate::core::{Rect, Widget, WidgetState};
+use crate::graphics::Canvas;
+
+/// An invisible area that moves the window when dragged, e.g. over the
+/// title bar drawn by a skin for an undecorated window.
+#[derive(Default)]
+pub struct DragRegion;
+
+impl DragRegion {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Widget for DragRegion {
+    fn draw(&self, _canvas: &mut Canvas, _bounds: &Rect, _state: WidgetState) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
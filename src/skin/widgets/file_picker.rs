@@ -7,10 +7,11 @@ use std::any::Any;
 use std::fs;
 use std::path::PathBuf;
 
-use image::RgbImage;
-
 use crate::core::{Rect, Widget, WidgetEvent, WidgetState};
-use crate::graphics::{draw_text, Canvas, TextStyle};
+use crate::graphics::text::DEFAULT_FONT_SIZE;
+use crate::graphics::{draw_text_sized, Canvas, Font, Sprite, TextEffects, TextStyle};
+
+use super::elide_start;
 
 /// An entry in the file list.
 #[derive(Debug, Clone)]
@@ -30,19 +31,19 @@ pub struct FilePicker {
     height: u32,
 
     // Directory picker images
-    picker_normal: RgbImage,
-    picker_hover: RgbImage,
-    picker_btn_normal: RgbImage,
-    picker_btn_hover: RgbImage,
+    picker_normal: Sprite,
+    picker_hover: Sprite,
+    picker_btn_normal: Sprite,
+    picker_btn_hover: Sprite,
 
     // Scrollbar images
-    track_image: RgbImage,
-    thumb_image: RgbImage,
+    track_image: Sprite,
+    thumb_image: Sprite,
 
     // List item images
-    item_normal: RgbImage,
-    item_hover: RgbImage,
-    item_selected: RgbImage,
+    item_normal: Sprite,
+    item_hover: Sprite,
+    item_selected: Sprite,
 
     /// Currently selected directory.
     selected_dir: Option<PathBuf>,
@@ -64,8 +65,14 @@ pub struct FilePicker {
     /// Width of the scrollbar.
     scrollbar_width: u32,
 
+    /// Font for the picker path and file names.
+    font: Font,
+    /// Font size.
+    font_size: f32,
     /// Text color for file names.
     text_color: u32,
+    /// Shadow, outline and glow drawn behind the text.
+    text_effects: TextEffects,
     /// Text color for directory names.
     dir_color: u32,
     /// Padding inside items.
@@ -96,15 +103,15 @@ impl FilePicker {
     pub fn new(
         width: u32,
         height: u32,
-        picker_normal: RgbImage,
-        picker_hover: RgbImage,
-        picker_btn_normal: RgbImage,
-        picker_btn_hover: RgbImage,
-        track_image: RgbImage,
-        thumb_image: RgbImage,
-        item_normal: RgbImage,
-        item_hover: RgbImage,
-        item_selected: RgbImage,
+        picker_normal: Sprite,
+        picker_hover: Sprite,
+        picker_btn_normal: Sprite,
+        picker_btn_hover: Sprite,
+        track_image: Sprite,
+        thumb_image: Sprite,
+        item_normal: Sprite,
+        item_hover: Sprite,
+        item_selected: Sprite,
     ) -> Self {
         let picker_height = picker_normal.height();
         let scrollbar_width = track_image.width();
@@ -131,7 +138,10 @@ impl FilePicker {
             item_height,
             picker_height,
             scrollbar_width,
+            font: Font::default(),
+            font_size: DEFAULT_FONT_SIZE,
             text_color: 0xDDDDDD,
+            text_effects: TextEffects::default(),
             dir_color: 0x88AAFF,
             padding: 8,
             binding: None,
@@ -151,12 +161,30 @@ impl FilePicker {
         self
     }
 
+    /// Set the font.
+    pub fn with_font(mut self, font: Font) -> Self {
+        self.font = font;
+        self
+    }
+
+    /// Set the font size.
+    pub fn with_font_size(mut self, size: f32) -> Self {
+        self.font_size = size;
+        self
+    }
+
     /// Set the text color.
     pub fn with_text_color(mut self, color: u32) -> Self {
         self.text_color = color;
         self
     }
 
+    /// Set the shadow, outline and glow drawn behind the text.
+    pub fn with_text_effects(mut self, effects: TextEffects) -> Self {
+        self.text_effects = effects;
+        self
+    }
+
     /// Set the directory color.
     pub fn with_dir_color(mut self, color: u32) -> Self {
         self.dir_color = color;
@@ -297,8 +325,8 @@ impl FilePicker {
         Rect::new(
             bounds.x,
             bounds.y + self.picker_height as i32,
-            self.width - self.scrollbar_width,
-            self.height - self.picker_height,
+            self.width.saturating_sub(self.scrollbar_width),
+            self.height.saturating_sub(self.picker_height),
         )
     }
 
@@ -309,7 +337,7 @@ impl FilePicker {
 
     /// Get the visible list height.
     fn list_height(&self) -> u32 {
-        self.height - self.picker_height
+        self.height.saturating_sub(self.picker_height)
     }
 
     /// Get the maximum scroll offset.
@@ -346,22 +374,8 @@ impl FilePicker {
     }
 
     /// Draw an image at a position with clipping.
-    fn draw_image(&self, canvas: &mut Canvas, image: &RgbImage, x: i32, y: i32, clip: Option<&Rect>) {
-        for (ix, iy, pixel) in image.enumerate_pixels() {
-            let px = x + ix as i32;
-            let py = y + iy as i32;
-
-            if let Some(clip) = clip {
-                if px < clip.x || px >= clip.right() || py < clip.y || py >= clip.bottom() {
-                    continue;
-                }
-            }
-
-            if px >= 0 && py >= 0 {
-                let [r, g, b] = pixel.0;
-                canvas.set_pixel_rgb(px as u32, py as u32, r, g, b);
-            }
-        }
+    fn draw_image(&self, canvas: &mut Canvas, image: &Sprite, x: i32, y: i32, clip: Option<&Rect>) {
+        image.draw(canvas, x, y, clip);
     }
 
     /// Draw the directory picker area.
@@ -374,17 +388,18 @@ impl FilePicker {
         } else {
             &self.picker_normal
         };
-        self.draw_image(canvas, bg, bounds.x, bounds.y, Some(&picker_bounds));
+        bg.draw_in(canvas, &picker_bounds, None);
 
         // Draw picker button
         let btn_width = self.picker_btn_normal.width();
-        let btn_x = bounds.x + (self.width - btn_width) as i32;
+        let btn_x = bounds.x + self.width.saturating_sub(btn_width) as i32;
         let btn = if self.picker_btn_hovered {
             &self.picker_btn_hover
         } else {
             &self.picker_btn_normal
         };
-        self.draw_image(canvas, btn, btn_x, bounds.y, Some(&picker_bounds));
+        let btn_rect = Rect::new(btn_x, bounds.y, btn_width, self.picker_height);
+        btn.draw_in(canvas, &btn_rect, None);
 
         // Draw directory path text
         let text_x = bounds.x + self.padding as i32;
@@ -396,47 +411,58 @@ impl FilePicker {
         };
 
         // Truncate if needed
-        let display_text = if text.len() > 60 {
-            format!("...{}", &text[text.len() - 57..])
-        } else {
-            text
-        };
+        let display_text = elide_start(text, 60);
 
-        let style = TextStyle { color: self.text_color };
+        let style = TextStyle::with_color(self.text_color).with_effects(self.text_effects);
         let text_clip = Rect::new(
             text_x,
             bounds.y,
-            self.width - btn_width - self.padding * 2,
+            self.width.saturating_sub(btn_width + self.padding * 2),
             self.picker_height,
         );
-        draw_text(canvas, text_x, text_y, Some(&text_clip), &display_text, style);
+        draw_text_sized(
+            canvas,
+            &self.font,
+            text_x,
+            text_y,
+            Some(&text_clip),
+            &display_text,
+            style,
+            self.font_size,
+        );
     }
 
     /// Draw the scrollbar.
     fn draw_scrollbar(&self, canvas: &mut Canvas, bounds: &Rect) {
-        let track_x = bounds.x + (self.width - self.scrollbar_width) as i32;
+        let track_x = bounds.x + self.width.saturating_sub(self.scrollbar_width) as i32;
         let track_y = bounds.y + self.picker_height as i32;
         let track_h = self.list_height();
 
-        // Tile track image
-        let img_h = self.track_image.height();
-        let mut y = track_y;
-        while y < track_y + track_h as i32 {
-            let remaining = (track_y + track_h as i32 - y) as u32;
-            let draw_h = remaining.min(img_h);
-
-            for (ix, iy, pixel) in self.track_image.enumerate_pixels() {
-                if iy >= draw_h {
-                    continue;
-                }
-                let px = track_x + ix as i32;
-                let py = y + iy as i32;
-                if px >= 0 && py >= 0 {
-                    let [r, g, b] = pixel.0;
-                    canvas.set_pixel_rgb(px as u32, py as u32, r, g, b);
+        if self.track_image.is_scalable() {
+            // Nine-slice tracks scale to the full list height
+            let track = Rect::new(track_x, track_y, self.scrollbar_width, track_h);
+            self.track_image.draw_in(canvas, &track, None);
+        } else {
+            // Tile track image
+            let img_h = self.track_image.height();
+            let mut y = track_y;
+            while y < track_y + track_h as i32 {
+                let remaining = (track_y + track_h as i32 - y) as u32;
+                let draw_h = remaining.min(img_h);
+
+                for (ix, iy, pixel) in self.track_image.image().enumerate_pixels() {
+                    if iy >= draw_h {
+                        continue;
+                    }
+                    let px = track_x + ix as i32;
+                    let py = y + iy as i32;
+                    if px >= 0 && py >= 0 {
+                        let [r, g, b, a] = pixel.0;
+                        canvas.blend_pixel_rgba(px as u32, py as u32, r, g, b, a);
+                    }
                 }
+                y += img_h as i32;
             }
-            y += img_h as i32;
         }
 
         // Draw thumb
@@ -449,10 +475,10 @@ impl FilePicker {
         let list_area = self.list_area(bounds);
 
         // Set clip for list area
-        canvas.set_clip(Some(list_area));
+        canvas.push_clip(list_area);
 
         let list_y = bounds.y + self.picker_height as i32;
-        let item_width = self.width - self.scrollbar_width;
+        let item_width = self.width.saturating_sub(self.scrollbar_width);
 
         for (i, entry) in self.entries.iter().enumerate() {
             let item_y = list_y + (i as i32 * self.item_height as i32) - self.scroll_y as i32;
@@ -471,16 +497,25 @@ impl FilePicker {
                 &self.item_normal
             };
 
-            // Draw item background (stretch horizontally if needed)
-            let img_width = bg.width();
-            let scale_x = item_width as f32 / img_width as f32;
-
-            for (ix, iy, pixel) in bg.enumerate_pixels() {
-                let px = bounds.x + (ix as f32 * scale_x) as i32;
-                let py = item_y + iy as i32;
-                if px >= list_area.x && px < list_area.right() && py >= list_area.y && py < list_area.bottom() {
-                    let [r, g, b] = pixel.0;
-                    canvas.set_pixel_rgb(px as u32, py as u32, r, g, b);
+            // Draw item background (nine-slice fills the row, otherwise stretch horizontally)
+            if bg.is_scalable() {
+                let item_rect = Rect::new(bounds.x, item_y, item_width, self.item_height);
+                bg.draw_in(canvas, &item_rect, Some(&list_area));
+            } else {
+                let img_width = bg.width();
+                let scale_x = item_width as f32 / img_width as f32;
+
+                for (ix, iy, pixel) in bg.image().enumerate_pixels() {
+                    let px = bounds.x + (ix as f32 * scale_x) as i32;
+                    let py = item_y + iy as i32;
+                    if px >= list_area.x
+                        && px < list_area.right()
+                        && py >= list_area.y
+                        && py < list_area.bottom()
+                    {
+                        let [r, g, b, a] = pixel.0;
+                        canvas.blend_pixel_rgba(px as u32, py as u32, r, g, b, a);
+                    }
                 }
             }
 
@@ -492,12 +527,21 @@ impl FilePicker {
             let display_name = format!("{}{}", prefix, entry.name);
             let color = if entry.is_dir { self.dir_color } else { self.text_color };
 
-            let style = TextStyle { color };
-            draw_text(canvas, text_x, text_y, Some(&list_area), &display_name, style);
+            let style = TextStyle::with_color(color).with_effects(self.text_effects);
+            draw_text_sized(
+                canvas,
+                &self.font,
+                text_x,
+                text_y,
+                Some(&list_area),
+                &display_name,
+                style,
+                self.font_size,
+            );
         }
 
-        // Clear clip
-        canvas.set_clip(None);
+        // Restore the previous clip
+        canvas.pop_clip();
     }
 }
 
@@ -605,5 +649,9 @@ impl Widget for FilePicker {
 
     fn set_bounds(&mut self, bounds: Rect) {
         self.current_bounds = Some(bounds);
+        // Follow resizes, keeping the list scrolled within its new length
+        self.width = bounds.width;
+        self.height = bounds.height;
+        self.scroll_y = self.scroll_y.min(self.max_scroll());
     }
 }
@@ -1,5 +1,6 @@
 mod checkbox;
 mod directory_picker;
+mod drag_region;
 mod file_picker;
 mod skin_button;
 mod skin_image;
@@ -9,9 +10,22 @@ mod text_input;
 
 pub use checkbox::Checkbox;
 pub use directory_picker::DirectoryPicker;
+pub use drag_region::DragRegion;
 pub use file_picker::FilePicker;
 pub use skin_button::SkinButton;
 pub use skin_image::SkinImage;
 pub use skin_vscroll::SkinVScroll;
 pub use static_text::StaticText;
 pub use text_input::TextInput;
+
+/// Keep the last characters of `text` so it's at most `max_chars` long,
+/// replacing the rest with "...".
+fn elide_start(text: String, max_chars: usize) -> String {
+    let count = text.chars().count();
+    if count <= max_chars {
+        return text;
+    }
+    let keep = max_chars.saturating_sub(3);
+    let tail: String = text.chars().skip(count - keep).collect();
+    format!("...{}", tail)
+}
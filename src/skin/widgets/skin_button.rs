@@ -1,28 +1,28 @@
 use std::any::Any;
-
-use image::RgbImage;
+use std::time::Instant;
 
 use crate::core::{Rect, Widget, WidgetEvent, WidgetState};
-use crate::graphics::Canvas;
+use crate::graphics::{Canvas, Playhead, Sprite};
+
+const STATE_NORMAL: usize = 0;
+const STATE_HOVER: usize = 1;
+const STATE_PRESSED: usize = 2;
 
 /// A button widget driven by skin assets for each state.
 pub struct SkinButton {
-    normal: RgbImage,
-    hover: RgbImage,
-    pressed: RgbImage,
+    normal: Sprite,
+    hover: Sprite,
+    pressed: Sprite,
     width: u32,
     height: u32,
     action: Option<String>,
+    /// Restarts state animations when the button changes state.
+    playhead: Playhead,
 }
 
 impl SkinButton {
     /// Create a skin button with images for each state.
-    pub fn new(
-        normal: RgbImage,
-        hover: RgbImage,
-        pressed: RgbImage,
-        action: Option<String>,
-    ) -> Self {
+    pub fn new(normal: Sprite, hover: Sprite, pressed: Sprite, action: Option<String>) -> Self {
         let width = normal.width();
         let height = normal.height();
         Self {
@@ -32,6 +32,7 @@ impl SkinButton {
             width,
             height,
             action,
+            playhead: Playhead::new(),
         }
     }
 
@@ -40,33 +41,33 @@ impl SkinButton {
         self.action.as_deref()
     }
 
-    fn draw_image(&self, canvas: &mut Canvas, bounds: &Rect, image: &RgbImage) {
-        for (ix, iy, pixel) in image.enumerate_pixels() {
-            let x = bounds.x + ix as i32;
-            let y = bounds.y + iy as i32;
-
-            // Clip to bounds
-            if x >= bounds.x && x < bounds.right() && y >= bounds.y && y < bounds.bottom() {
-                if x >= 0 && y >= 0 {
-                    let [r, g, b] = pixel.0;
-                    canvas.set_pixel_rgb(x as u32, y as u32, r, g, b);
-                }
-            }
+    /// Get the sprite for a state key.
+    fn sprite(&self, state: usize) -> &Sprite {
+        match state {
+            STATE_PRESSED => &self.pressed,
+            STATE_HOVER => &self.hover,
+            _ => &self.normal,
         }
     }
 }
 
 impl Widget for SkinButton {
     fn draw(&self, canvas: &mut Canvas, bounds: &Rect, state: WidgetState) {
-        let image = if state.pressed {
-            &self.pressed
+        let key = if state.pressed {
+            STATE_PRESSED
         } else if state.hovered {
-            &self.hover
+            STATE_HOVER
         } else {
-            &self.normal
+            STATE_NORMAL
         };
 
-        self.draw_image(canvas, bounds, image);
+        let elapsed = self.playhead.elapsed(key);
+        self.sprite(key).draw_in_at(canvas, bounds, None, elapsed);
+    }
+
+    fn next_frame(&self) -> Option<Instant> {
+        let key = self.playhead.current()?;
+        self.playhead.next_frame(self.sprite(key))
     }
 
     fn preferred_size(&self) -> (u32, u32) {
@@ -1,44 +1,39 @@
 use std::any::Any;
-
-use image::RgbImage;
+use std::time::Instant;
 
 use crate::core::{Rect, Widget, WidgetState};
-use crate::graphics::Canvas;
+use crate::graphics::{Canvas, Playhead, Sprite};
 
-/// A static image widget driven by a skin asset.
+/// An image widget driven by a skin asset (static or animated).
 pub struct SkinImage {
-    image: RgbImage,
+    image: Sprite,
     width: u32,
     height: u32,
+    playhead: Playhead,
 }
 
 impl SkinImage {
-    /// Create a skin image widget from loaded RGB image data.
-    pub fn new(image: RgbImage) -> Self {
+    /// Create a skin image widget from loaded RGBA image data.
+    pub fn new(image: Sprite) -> Self {
         let width = image.width();
         let height = image.height();
         Self {
             image,
             width,
             height,
+            playhead: Playhead::new(),
         }
     }
 }
 
 impl Widget for SkinImage {
     fn draw(&self, canvas: &mut Canvas, bounds: &Rect, _state: WidgetState) {
-        for (ix, iy, pixel) in self.image.enumerate_pixels() {
-            let x = bounds.x + ix as i32;
-            let y = bounds.y + iy as i32;
-
-            // Clip to bounds
-            if x >= bounds.x && x < bounds.right() && y >= bounds.y && y < bounds.bottom() {
-                if x >= 0 && y >= 0 {
-                    let [r, g, b] = pixel.0;
-                    canvas.set_pixel_rgb(x as u32, y as u32, r, g, b);
-                }
-            }
-        }
+        let elapsed = self.playhead.elapsed(0);
+        self.image.draw_in_at(canvas, bounds, None, elapsed);
+    }
+
+    fn next_frame(&self) -> Option<Instant> {
+        self.playhead.next_frame(&self.image)
     }
 
     fn preferred_size(&self) -> (u32, u32) {
@@ -4,10 +4,8 @@
 
 use std::any::Any;
 
-use image::RgbImage;
-
 use crate::core::{Rect, Widget, WidgetEvent, WidgetState};
-use crate::graphics::Canvas;
+use crate::graphics::{Canvas, Sprite};
 
 /// A skinned vertical scroll container with image-based scrollbar.
 pub struct SkinVScroll {
@@ -25,19 +23,14 @@ pub struct SkinVScroll {
     /// Scroll speed multiplier.
     scroll_speed: f32,
     /// Track image (tiled or stretched vertically).
-    track_image: RgbImage,
+    track_image: Sprite,
     /// Thumb image.
-    thumb_image: RgbImage,
+    thumb_image: Sprite,
 }
 
 impl SkinVScroll {
     /// Create a new skinned vertical scroll container.
-    pub fn new(
-        width: u32,
-        height: u32,
-        track_image: RgbImage,
-        thumb_image: RgbImage,
-    ) -> Self {
+    pub fn new(width: u32, height: u32, track_image: Sprite, thumb_image: Sprite) -> Self {
         let scrollbar_width = track_image.width();
         Self {
             width,
@@ -124,20 +117,21 @@ impl SkinVScroll {
     }
 
     /// Draw an image at a position, respecting canvas clipping.
-    fn draw_image(&self, canvas: &mut Canvas, image: &RgbImage, x: i32, y: i32) {
-        for (ix, iy, pixel) in image.enumerate_pixels() {
-            let px = x + ix as i32;
-            let py = y + iy as i32;
-            if px >= 0 && py >= 0 {
-                let [r, g, b] = pixel.0;
-                canvas.set_pixel_rgb(px as u32, py as u32, r, g, b);
-            }
-        }
+    fn draw_image(&self, canvas: &mut Canvas, image: &Sprite, x: i32, y: i32) {
+        image.draw(canvas, x, y, None);
     }
 
     /// Draw the track image, tiling vertically if needed.
     fn draw_track(&self, canvas: &mut Canvas, bounds: &Rect) {
         let track_x = bounds.x + self.viewport_width() as i32;
+
+        // Nine-slice tracks scale to the full track height
+        if self.track_image.is_scalable() {
+            let track = Rect::new(track_x, bounds.y, self.scrollbar_width, self.height);
+            self.track_image.draw_in(canvas, &track, None);
+            return;
+        }
+
         let track_height = self.track_image.height();
 
         // Tile the track image vertically
@@ -147,15 +141,15 @@ impl SkinVScroll {
             let remaining = (bounds.y + self.height as i32 - y) as u32;
             let draw_height = remaining.min(track_height);
 
-            for (ix, iy, pixel) in self.track_image.enumerate_pixels() {
+            for (ix, iy, pixel) in self.track_image.image().enumerate_pixels() {
                 if iy >= draw_height {
                     continue;
                 }
                 let px = track_x + ix as i32;
                 let py = y + iy as i32;
                 if px >= 0 && py >= 0 {
-                    let [r, g, b] = pixel.0;
-                    canvas.set_pixel_rgb(px as u32, py as u32, r, g, b);
+                    let [r, g, b, a] = pixel.0;
+                    canvas.blend_pixel_rgba(px as u32, py as u32, r, g, b, a);
                 }
             }
             y += track_height as i32;
@@ -185,7 +179,7 @@ impl Widget for SkinVScroll {
             self.viewport_width(),
             self.viewport_height(),
         );
-        canvas.set_clip(Some(viewport));
+        canvas.push_clip(viewport);
 
         // Draw child with scroll offset
         if let Some(ref child) = self.child {
@@ -198,8 +192,8 @@ impl Widget for SkinVScroll {
             child.draw(canvas, &child_bounds, WidgetState::default());
         }
 
-        // Clear clip
-        canvas.set_clip(None);
+        // Restore the previous clip
+        canvas.pop_clip();
     }
 
     fn preferred_size(&self) -> (u32, u32) {
@@ -1,28 +1,50 @@
 use std::any::Any;
+use std::cell::{Cell, Ref, RefCell};
 
 use crate::core::{Rect, Widget, WidgetEvent, WidgetState};
 use crate::graphics::{
-    caret_x_sized, draw_text_sized, line_height_sized, Canvas, TextStyle,
+    draw_text_sized, line_height_sized, measure_text, Canvas, Font, TextEffects, TextStyle,
 };
-use crate::skin::types::{TextAlign, VerticalAlign};
+use crate::skin::types::{TextAlign, TextWrap, VerticalAlign};
 
 /// A static text widget for displaying non-editable text.
 /// Can be bound to a Store key to display dynamic values.
 pub struct StaticText {
     /// The text content to display.
     content: String,
+    /// Font used for the text.
+    font: Font,
     /// Font size in pixels.
     font_size: f32,
     /// Text color.
     text_color: u32,
+    /// Shadow, outline and glow drawn behind the text.
+    text_effects: TextEffects,
     /// Horizontal alignment.
     text_align: TextAlign,
-    /// Vertical alignment.
+    /// Vertical alignment of the block of lines.
     vertical_align: VerticalAlign,
+    /// How lines wider than the content area are broken.
+    wrap: TextWrap,
+    /// Distance between lines as a multiple of the line height.
+    line_spacing: f32,
+    /// Lines shown before the rest is cut off with an ellipsis.
+    max_lines: Option<u32>,
     /// Padding from edges.
     padding: u32,
     /// Store binding key for reading values.
     binding: Option<String>,
+    /// Lines from the last layout, reused until the content or width changes.
+    wrapped: RefCell<Option<WrappedLines>>,
+    /// Preferred size of the content, kept apart from the drawn lines so
+    /// measuring doesn't throw away their layout.
+    measured: Cell<Option<(u32, u32)>>,
+}
+
+/// The content broken into lines for one content width.
+struct WrappedLines {
+    width: u32,
+    lines: Vec<String>,
 }
 
 impl StaticText {
@@ -30,15 +52,28 @@ impl StaticText {
     pub fn new(content: String) -> Self {
         Self {
             content,
+            font: Font::default(),
             font_size: 16.0,
             text_color: 0x000000, // Black
+            text_effects: TextEffects::default(),
             text_align: TextAlign::Left,
             vertical_align: VerticalAlign::Center,
+            wrap: TextWrap::None,
+            line_spacing: 1.0,
+            max_lines: None,
             padding: 0,
             binding: None,
+            wrapped: RefCell::new(None),
+            measured: Cell::new(None),
         }
     }
 
+    /// Set the font.
+    pub fn with_font(mut self, font: Font) -> Self {
+        self.font = font;
+        self
+    }
+
     /// Set the font size.
     pub fn with_font_size(mut self, size: f32) -> Self {
         self.font_size = size;
@@ -51,6 +86,12 @@ impl StaticText {
         self
     }
 
+    /// Set the shadow, outline and glow drawn behind the text.
+    pub fn with_text_effects(mut self, effects: TextEffects) -> Self {
+        self.text_effects = effects;
+        self
+    }
+
     /// Set the horizontal alignment.
     pub fn with_text_align(mut self, align: TextAlign) -> Self {
         self.text_align = align;
@@ -63,6 +104,24 @@ impl StaticText {
         self
     }
 
+    /// Set the line wrapping mode.
+    pub fn with_wrap(mut self, wrap: TextWrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Set the line spacing, as a multiple of the line height.
+    pub fn with_line_spacing(mut self, spacing: f32) -> Self {
+        self.line_spacing = spacing;
+        self
+    }
+
+    /// Set the maximum number of lines shown.
+    pub fn with_max_lines(mut self, max_lines: u32) -> Self {
+        self.max_lines = Some(max_lines);
+        self
+    }
+
     /// Set the padding.
     pub fn with_padding(mut self, padding: u32) -> Self {
         self.padding = padding;
@@ -88,11 +147,135 @@ impl StaticText {
     /// Set the text content.
     pub fn set_content(&mut self, content: String) {
         self.content = content;
+        self.wrapped.replace(None);
+        self.measured.set(None);
+    }
+
+    /// Measure the width of a line of text.
+    fn text_width(&self, text: &str) -> u32 {
+        measure_text(&self.font, text, self.font_size).0
     }
 
-    /// Measure the width of the text.
-    fn text_width(&self) -> u32 {
-        caret_x_sized(&self.content, self.content.chars().count(), self.font_size)
+    /// Distance from the top of one line to the top of the next.
+    fn line_advance(&self) -> i32 {
+        let line_height = line_height_sized(&self.font, self.font_size);
+        (line_height as f32 * self.line_spacing).round() as i32
+    }
+
+    /// Height of `count` lines, from the top of the first to the bottom of the last.
+    fn block_height(&self, count: usize) -> i32 {
+        let line_height = line_height_sized(&self.font, self.font_size) as i32;
+        line_height + self.line_advance() * (count as i32 - 1).max(0)
+    }
+
+    /// The lines drawn within `max_width`, laid out again only when the
+    /// width or the content changed.
+    fn lines(&self, max_width: u32) -> Ref<'_, [String]> {
+        let stale = self
+            .wrapped
+            .borrow()
+            .as_ref()
+            .is_none_or(|w| w.width != max_width);
+        if stale {
+            let lines = self.layout_lines(max_width);
+            self.wrapped.replace(Some(WrappedLines {
+                width: max_width,
+                lines,
+            }));
+        }
+        Ref::map(self.wrapped.borrow(), |wrapped| {
+            wrapped.as_ref().map_or(&[][..], |w| w.lines.as_slice())
+        })
+    }
+
+    /// Break the content into the lines drawn within `max_width`.
+    fn layout_lines(&self, max_width: u32) -> Vec<String> {
+        let mut lines = Vec::new();
+        for paragraph in self.content.split('\n') {
+            match self.wrap {
+                TextWrap::None => lines.push(paragraph.to_string()),
+                TextWrap::Word => self.wrap_words(paragraph, max_width, &mut lines),
+                TextWrap::Char => self.wrap_chars(paragraph, max_width, &mut lines),
+            }
+        }
+
+        if let Some(max_lines) = self.max_lines {
+            let cut = lines.len() > max_lines as usize;
+            lines.truncate(max_lines.max(1) as usize);
+            let last = lines.len() - 1;
+            for (i, line) in lines.iter_mut().enumerate() {
+                // Unwrapped lines that overflow are cut off like the last line
+                let overflows = self.wrap == TextWrap::None && self.text_width(line) > max_width;
+                if (cut && i == last) || overflows {
+                    *line = self.ellipsize(line, max_width);
+                }
+            }
+        }
+        lines
+    }
+
+    /// Fill lines word by word. Words wider than a whole line are broken
+    /// between characters.
+    fn wrap_words(&self, paragraph: &str, max_width: u32, lines: &mut Vec<String>) {
+        let mut line = String::new();
+        for word in paragraph.split_inclusive(' ') {
+            let candidate = format!("{}{}", line, word);
+            if line.is_empty() || self.text_width(candidate.trim_end()) <= max_width {
+                line = candidate;
+            } else {
+                lines.push(line.trim_end().to_string());
+                line = word.to_string();
+            }
+
+            if self.text_width(line.trim_end()) > max_width {
+                let mut pieces = Vec::new();
+                self.wrap_chars(&line, max_width, &mut pieces);
+                line = pieces.pop().unwrap_or_default();
+                lines.extend(pieces);
+            }
+        }
+        lines.push(line.trim_end().to_string());
+    }
+
+    /// Fill lines character by character.
+    fn wrap_chars(&self, paragraph: &str, max_width: u32, lines: &mut Vec<String>) {
+        let mut rest = paragraph;
+        loop {
+            // Byte ends of the prefixes of one, two, ... characters
+            let ends: Vec<usize> = rest
+                .char_indices()
+                .skip(1)
+                .map(|(i, _)| i)
+                .chain([rest.len()])
+                .collect();
+            // The longest prefix that fits, but at least one character
+            let fitting = ends.partition_point(|&end| self.text_width(&rest[..end]) <= max_width);
+            let end = ends[fitting.saturating_sub(1)];
+            lines.push(rest[..end].to_string());
+            rest = &rest[end..];
+            if rest.is_empty() {
+                return;
+            }
+        }
+    }
+
+    /// Shorten `line` until it fits `max_width` with an ellipsis after it.
+    fn ellipsize(&self, line: &str, max_width: u32) -> String {
+        // Pixel fonts often lack the ellipsis character
+        let ellipsis = if self.font.has_glyph('\u{2026}') {
+            "\u{2026}"
+        } else {
+            "..."
+        };
+        let mut line = line.trim_end().to_string();
+        loop {
+            let candidate = format!("{}{}", line, ellipsis);
+            if line.is_empty() || self.text_width(&candidate) <= max_width {
+                return candidate;
+            }
+            line.pop();
+            line.truncate(line.trim_end().len());
+        }
     }
 }
 
@@ -106,47 +289,58 @@ impl Widget for StaticText {
             bounds.height.saturating_sub(self.padding * 2),
         );
 
-        let text_height = line_height_sized(self.font_size);
-        let text_width = self.text_width();
+        let lines = self.lines(content_rect.width);
+        let block_height = self.block_height(lines.len());
 
-        // Calculate x position based on horizontal alignment
-        let text_x = match self.text_align {
-            TextAlign::Left => content_rect.x,
-            TextAlign::Center => {
-                content_rect.x + (content_rect.width as i32 - text_width as i32) / 2
-            }
-            TextAlign::Right => {
-                content_rect.x + content_rect.width as i32 - text_width as i32
-            }
-        };
-
-        // Calculate y position based on vertical alignment
-        let text_y = match self.vertical_align {
+        // Calculate y position of the block based on vertical alignment
+        let block_y = match self.vertical_align {
             VerticalAlign::Top => content_rect.y,
             VerticalAlign::Center => {
-                content_rect.y + (content_rect.height as i32 - text_height as i32) / 2
-            }
-            VerticalAlign::Bottom => {
-                content_rect.y + content_rect.height as i32 - text_height as i32
+                content_rect.y + (content_rect.height as i32 - block_height) / 2
             }
+            VerticalAlign::Bottom => content_rect.y + content_rect.height as i32 - block_height,
         };
 
-        // Draw text clipped to content rect
-        draw_text_sized(
-            canvas,
-            text_x,
-            text_y,
-            Some(&content_rect),
-            &self.content,
-            TextStyle::with_color(self.text_color),
-            self.font_size,
-        );
+        for (i, line) in lines.iter().enumerate() {
+            let text_width = self.text_width(line);
+
+            // Calculate x position based on horizontal alignment
+            let text_x = match self.text_align {
+                TextAlign::Left => content_rect.x,
+                TextAlign::Center => {
+                    content_rect.x + (content_rect.width as i32 - text_width as i32) / 2
+                }
+                TextAlign::Right => content_rect.x + content_rect.width as i32 - text_width as i32,
+            };
+
+            // Draw text clipped to content rect
+            draw_text_sized(
+                canvas,
+                &self.font,
+                text_x,
+                block_y + self.line_advance() * i as i32,
+                Some(&content_rect),
+                line,
+                TextStyle::with_color(self.text_color).with_effects(self.text_effects),
+                self.font_size,
+            );
+        }
     }
 
     fn preferred_size(&self) -> (u32, u32) {
-        let width = self.text_width() + self.padding * 2;
-        let height = line_height_sized(self.font_size) + self.padding * 2;
-        (width, height)
+        let (width, height) = self.measured.get().unwrap_or_else(|| {
+            // Without a width to fit, only newlines and max_lines break the text
+            let lines = self.layout_lines(u32::MAX);
+            let width = lines
+                .iter()
+                .map(|line| self.text_width(line))
+                .max()
+                .unwrap_or(0);
+            let size = (width, self.block_height(lines.len()).max(0) as u32);
+            self.measured.set(Some(size));
+            size
+        });
+        (width + self.padding * 2, height + self.padding * 2)
     }
 
     fn on_event(&mut self, _event: &WidgetEvent) -> bool {
@@ -161,3 +355,70 @@ impl Widget for StaticText {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn text(content: &str) -> StaticText {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("demo_app.crix/skin/font.ttf");
+        StaticText::new(content.to_string()).with_font(Font::load(&path).unwrap())
+    }
+
+    #[test]
+    fn test_wrapping_modes_and_ellipsis() {
+        let content = "the quick brown fox\njumps";
+        let measure = text("");
+        let width = measure
+            .text_width("the quick")
+            .max(measure.text_width("brown fox"));
+
+        assert_eq!(
+            text(content).layout_lines(width),
+            ["the quick brown fox", "jumps"]
+        );
+
+        let words = text(content).with_wrap(TextWrap::Word);
+        assert_eq!(
+            words.layout_lines(width),
+            ["the quick", "brown fox", "jumps"]
+        );
+
+        let chars = text("abcdef").with_wrap(TextWrap::Char);
+        let lines = chars.layout_lines(chars.text_width("abcd"));
+        assert_eq!(lines, ["abcd", "ef"]);
+
+        let clipped = text(content).with_wrap(TextWrap::Word).with_max_lines(2);
+        let lines = clipped.layout_lines(width);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("brown") && lines[1].ends_with("..."));
+        assert!(clipped.text_width(&lines[1]) <= width);
+    }
+
+    #[test]
+    fn test_unwrapped_overflow_is_ellipsized_and_layout_is_cached() {
+        let mut text = text("the quick brown fox\njumps").with_max_lines(3);
+        let width = text.text_width("the quick");
+
+        let lines = text.layout_lines(width);
+        assert!(lines[0].starts_with("the") && lines[0].ends_with("..."));
+        assert!(text.text_width(&lines[0]) <= width);
+        assert_eq!(lines[1], "jumps");
+
+        assert_eq!(&*text.lines(width), lines.as_slice());
+        // Measuring keeps the lines laid out for drawing
+        let (preferred, _) = text.preferred_size();
+        assert_eq!(preferred, text.text_width("the quick brown fox"));
+        assert!(
+            text.wrapped
+                .borrow()
+                .as_ref()
+                .is_some_and(|w| w.width == width)
+        );
+
+        text.set_content("fox".to_string());
+        assert_eq!(text.preferred_size().0, text.text_width("fox"));
+        assert_eq!(&*text.lines(width), ["fox"]);
+    }
+}
@@ -1,13 +1,12 @@
 use std::any::Any;
 use std::time::Instant;
 
-use image::RgbImage;
-
 use crate::core::{KeyCode, Rect, Widget, WidgetEvent, WidgetState};
 use crate::graphics::{
-    caret_x_sized, draw_caret, draw_text_sized,
-    line_height_sized, Canvas, TextStyle,
+    caret_positions_sized, caret_x_sized, draw_caret, draw_text_sized, line_height_sized, Canvas,
+    Font, Sprite, TextEffects, TextStyle,
 };
+use crate::graphics::text::DEFAULT_FONT_SIZE;
 use crate::skin::types::TextValidation;
 
 /// A text input widget for editable single-line text.
@@ -23,11 +22,11 @@ pub struct TextInput {
     /// Cursor position (0..=text.len()).
     cursor: usize,
     /// Background images for different states.
-    normal: RgbImage,
-    hover: RgbImage,
-    focused: RgbImage,
+    normal: Sprite,
+    hover: Sprite,
+    focused: Sprite,
     /// Optional invalid state background.
-    invalid: Option<RgbImage>,
+    invalid: Option<Sprite>,
     /// Widget dimensions.
     width: u32,
     height: u32,
@@ -35,9 +34,13 @@ pub struct TextInput {
     padding: u32,
     /// Text color.
     text_color: u32,
+    /// Shadow, outline and glow drawn behind the text.
+    text_effects: TextEffects,
     /// Caret color.
     caret_color: u32,
-    /// Custom font size (uses global if None).
+    /// Font used for the text.
+    font: Font,
+    /// Custom font size (uses `DEFAULT_FONT_SIZE` if None).
     font_size: Option<f32>,
     /// Maximum number of characters allowed.
     max_length: Option<u32>,
@@ -60,12 +63,7 @@ pub struct TextInput {
 
 impl TextInput {
     /// Create a new text input with the given state images.
-    pub fn new(
-        normal: RgbImage,
-        hover: RgbImage,
-        focused: RgbImage,
-        invalid: Option<RgbImage>,
-    ) -> Self {
+    pub fn new(normal: Sprite, hover: Sprite, focused: Sprite, invalid: Option<Sprite>) -> Self {
         let width = normal.width();
         let height = normal.height();
         Self {
@@ -79,7 +77,9 @@ impl TextInput {
             height,
             padding: 4,
             text_color: 0x000000, // Black text
+            text_effects: TextEffects::default(),
             caret_color: 0x000000,
+            font: Font::default(),
             font_size: None,
             max_length: None,
             validation: TextValidation::Any,
@@ -105,6 +105,12 @@ impl TextInput {
         self
     }
 
+    /// Set the shadow, outline and glow drawn behind the text.
+    pub fn with_text_effects(mut self, effects: TextEffects) -> Self {
+        self.text_effects = effects;
+        self
+    }
+
     /// Set the caret color.
     pub fn with_caret_color(mut self, color: u32) -> Self {
         self.caret_color = color;
@@ -123,6 +129,12 @@ impl TextInput {
         self
     }
 
+    /// Set the font.
+    pub fn with_font(mut self, font: Font) -> Self {
+        self.font = font;
+        self
+    }
+
     /// Set the font size.
     pub fn with_font_size(mut self, size: f32) -> Self {
         self.font_size = Some(size);
@@ -162,13 +174,9 @@ impl TextInput {
         self.dirty = false;
     }
 
-    /// Get the effective font size (custom or global).
+    /// Get the effective font size (custom or default).
     fn effective_font_size(&self) -> f32 {
-        self.font_size.unwrap_or_else(|| {
-            // Use global font size - we need to get it from the text module
-            // For now, default to 16.0 if no custom size
-            16.0
-        })
+        self.font_size.unwrap_or(DEFAULT_FONT_SIZE)
     }
 
     /// Get the current text value.
@@ -319,40 +327,18 @@ impl TextInput {
         let relative_x = (click_x - text_start_x).max(0) as u32;
         let size = self.effective_font_size();
 
-        // Find the character position closest to the click
-        let mut best_pos = 0;
-        let mut best_dist = relative_x;
-
-        for i in 0..=self.text.len() {
-            let char_x = caret_x_sized(&self.text, i, size);
-            let dist = if char_x > relative_x {
-                char_x - relative_x
-            } else {
-                relative_x - char_x
-            };
-            if dist < best_dist {
-                best_dist = dist;
-                best_pos = i;
-            }
-        }
+        // Find the character position closest to the click. Positions in
+        // right-to-left text decrease, so every one has to be checked.
+        let positions = caret_positions_sized(&self.font, &self.text, size);
+        let best_pos = positions
+            .into_iter()
+            .enumerate()
+            .min_by_key(|(_, char_x)| char_x.abs_diff(relative_x))
+            .map_or(0, |(i, _)| i);
 
         self.cursor = best_pos;
         self.reset_blink();
     }
-
-    fn draw_image(&self, canvas: &mut Canvas, bounds: &Rect, image: &RgbImage) {
-        for (ix, iy, pixel) in image.enumerate_pixels() {
-            let x = bounds.x + ix as i32;
-            let y = bounds.y + iy as i32;
-
-            if x >= bounds.x && x < bounds.right() && y >= bounds.y && y < bounds.bottom() {
-                if x >= 0 && y >= 0 {
-                    let [r, g, b] = pixel.0;
-                    canvas.set_pixel_rgb(x as u32, y as u32, r, g, b);
-                }
-            }
-        }
-    }
 }
 
 impl Widget for TextInput {
@@ -369,7 +355,7 @@ impl Widget for TextInput {
         };
 
         // Draw background
-        self.draw_image(canvas, bounds, image);
+        image.draw_in(canvas, bounds, None);
 
         // Calculate content rect (with padding)
         let content_rect = Rect::new(
@@ -379,9 +365,9 @@ impl Widget for TextInput {
             bounds.height.saturating_sub(self.padding * 2),
         );
 
-        // Get font size (custom or global)
+        // Get font size (custom or default)
         let font_size = self.effective_font_size();
-        let text_height = line_height_sized(font_size);
+        let text_height = line_height_sized(&self.font, font_size);
 
         // Center text vertically
         let text_y = content_rect.y + (content_rect.height as i32 - text_height as i32) / 2;
@@ -389,17 +375,18 @@ impl Widget for TextInput {
         // Draw text clipped to content rect
         draw_text_sized(
             canvas,
+            &self.font,
             content_rect.x,
             text_y,
             Some(&content_rect),
             &self.text,
-            TextStyle::with_color(self.text_color),
+            TextStyle::with_color(self.text_color).with_effects(self.text_effects),
             font_size,
         );
 
         // Draw caret if focused and visible
         if state.focused && self.caret_visible {
-            let caret_offset = caret_x_sized(&self.text, self.cursor, font_size);
+            let caret_offset = caret_x_sized(&self.font, &self.text, self.cursor, font_size);
             let caret_x_pos = content_rect.x + caret_offset as i32;
             draw_caret(
                 canvas,
@@ -0,0 +1,352 @@
+//! Winamp classic (.wsz) skin importer.
+//!
+//! A `.wsz` file is a zip archive of BMP sprite sheets (`main.bmp`,
+//! `cbuttons.bmp`, `titlebar.bmp`, ...) plus a few text files such as
+//! `pledit.txt`. The importer converts each sheet to PNG, declares every
+//! sprite as an atlas asset and lays the parts out at their classic
+//! main-window positions under well-known ids (`play`, `stop`, `close`, ...).
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use image::{ImageFormat, RgbaImage};
+use serde_json::{json, Value};
+use zip::ZipArchive;
+
+use super::types::{Skin, SkinError};
+
+/// Size of the classic main window.
+const MAIN_WIDTH: u32 = 275;
+const MAIN_HEIGHT: u32 = 116;
+
+/// Default text color when `pledit.txt` is missing or has no `Normal` entry.
+const DEFAULT_TEXT_COLOR: u32 = 0x00FF00;
+
+/// A static image cut from a sheet.
+struct ImageDef {
+    id: &'static str,
+    sheet: &'static str,
+    rect: [u32; 4],
+    pos: (i32, i32),
+    z: i32,
+}
+
+/// A push button with normal and pressed sprites on the same sheet.
+struct ButtonDef {
+    id: &'static str,
+    sheet: &'static str,
+    normal: [u32; 4],
+    pressed: [u32; 4],
+    pos: (i32, i32),
+}
+
+/// A two-state toggle (shuffle/repeat).
+struct ToggleDef {
+    id: &'static str,
+    sheet: &'static str,
+    off: [u32; 4],
+    on: [u32; 4],
+    pos: (i32, i32),
+}
+
+#[rustfmt::skip]
+const IMAGES: &[ImageDef] = &[
+    ImageDef { id: "main", sheet: "main", rect: [0, 0, MAIN_WIDTH, MAIN_HEIGHT], pos: (0, 0), z: 0 },
+    ImageDef { id: "titlebar", sheet: "titlebar", rect: [27, 0, 275, 14], pos: (0, 0), z: 1 },
+    ImageDef { id: "posbar", sheet: "posbar", rect: [0, 0, 248, 10], pos: (16, 72), z: 1 },
+    ImageDef { id: "volume", sheet: "volume", rect: [0, 0, 68, 13], pos: (107, 57), z: 1 },
+    ImageDef { id: "balance", sheet: "balance", rect: [9, 0, 38, 13], pos: (177, 57), z: 1 },
+];
+
+#[rustfmt::skip]
+const BUTTONS: &[ButtonDef] = &[
+    ButtonDef { id: "previous", sheet: "cbuttons", normal: [0, 0, 23, 18], pressed: [0, 18, 23, 18], pos: (16, 88) },
+    ButtonDef { id: "play", sheet: "cbuttons", normal: [23, 0, 23, 18], pressed: [23, 18, 23, 18], pos: (39, 88) },
+    ButtonDef { id: "pause", sheet: "cbuttons", normal: [46, 0, 23, 18], pressed: [46, 18, 23, 18], pos: (62, 88) },
+    ButtonDef { id: "stop", sheet: "cbuttons", normal: [69, 0, 23, 18], pressed: [69, 18, 23, 18], pos: (85, 88) },
+    ButtonDef { id: "next", sheet: "cbuttons", normal: [92, 0, 22, 18], pressed: [92, 18, 22, 18], pos: (108, 88) },
+    ButtonDef { id: "eject", sheet: "cbuttons", normal: [114, 0, 22, 16], pressed: [114, 16, 22, 16], pos: (136, 89) },
+    ButtonDef { id: "options", sheet: "titlebar", normal: [0, 0, 9, 9], pressed: [0, 9, 9, 9], pos: (6, 3) },
+    ButtonDef { id: "minimize", sheet: "titlebar", normal: [9, 0, 9, 9], pressed: [9, 9, 9, 9], pos: (244, 3) },
+    ButtonDef { id: "shade", sheet: "titlebar", normal: [0, 18, 9, 9], pressed: [9, 18, 9, 9], pos: (254, 3) },
+    ButtonDef { id: "close", sheet: "titlebar", normal: [18, 0, 9, 9], pressed: [18, 9, 9, 9], pos: (264, 3) },
+];
+
+#[rustfmt::skip]
+const TOGGLES: &[ToggleDef] = &[
+    ToggleDef { id: "shuffle", sheet: "shufrep", off: [28, 0, 47, 15], on: [28, 30, 47, 15], pos: (164, 89) },
+    ToggleDef { id: "repeat", sheet: "shufrep", off: [0, 0, 28, 15], on: [0, 30, 28, 15], pos: (210, 89) },
+];
+
+impl Skin {
+    /// Import a Winamp classic `.wsz` archive into a crix skin directory.
+    ///
+    /// Sheets are written to `out_dir/images` as PNG and a `skin.json` using
+    /// atlas assets is generated next to them. Sprites whose sheet is missing
+    /// from the archive are skipped; only `main.bmp` is required.
+    /// Returns the imported skin, loaded back from the generated `skin.json`.
+    pub fn import_wsz(archive: &Path, out_dir: &Path) -> Result<Self, SkinError> {
+        let name = archive
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Imported Skin".to_string());
+        let file = File::open(archive)?;
+        Self::import_wsz_from(file, &name, out_dir)
+    }
+
+    /// Import a `.wsz` archive from any seekable reader.
+    pub fn import_wsz_from<R: Read + Seek>(
+        reader: R,
+        name: &str,
+        out_dir: &Path,
+    ) -> Result<Self, SkinError> {
+        let files = read_archive(reader)?;
+
+        // Decode every sheet the layout needs
+        let mut sheets: HashMap<&str, RgbaImage> = HashMap::new();
+        let sheet_names = IMAGES
+            .iter()
+            .map(|d| d.sheet)
+            .chain(BUTTONS.iter().map(|d| d.sheet))
+            .chain(TOGGLES.iter().map(|d| d.sheet));
+        for sheet in sheet_names {
+            if sheets.contains_key(sheet) {
+                continue;
+            }
+            if let Some(bytes) = files.get(&format!("{}.bmp", sheet)) {
+                let img = image::load_from_memory_with_format(bytes, ImageFormat::Bmp)?;
+                sheets.insert(sheet, img.to_rgba8());
+            }
+        }
+        if !sheets.contains_key("main") {
+            return Err(SkinError::AssetNotFound("main.bmp".to_string()));
+        }
+
+        let text_color = files
+            .get("pledit.txt")
+            .and_then(|bytes| pledit_color(&String::from_utf8_lossy(bytes), "normal"))
+            .unwrap_or(DEFAULT_TEXT_COLOR);
+
+        // Write sheets as PNG
+        let images_dir = out_dir.join("images");
+        fs::create_dir_all(&images_dir)?;
+        for (sheet, img) in &sheets {
+            img.save_with_format(images_dir.join(format!("{}.png", sheet)), ImageFormat::Png)?;
+        }
+
+        let mut assets = serde_json::Map::new();
+        let mut parts = Vec::new();
+        let fits = |sheet: &str, [x, y, w, h]: [u32; 4]| {
+            sheets
+                .get(sheet)
+                .is_some_and(|img| x + w <= img.width() && y + h <= img.height())
+        };
+        let atlas = |sheet: &str, rect: [u32; 4]| {
+            json!({ "sheet": format!("images/{}.png", sheet), "rect": rect })
+        };
+
+        for def in IMAGES.iter().filter(|d| fits(d.sheet, d.rect)) {
+            assets.insert(def.id.to_string(), atlas(def.sheet, def.rect));
+            parts.push(json!({
+                "id": def.id,
+                "type": "image",
+                "asset": def.id,
+                "x": def.pos.0,
+                "y": def.pos.1,
+                "width": def.rect[2],
+                "height": def.rect[3],
+                "z": def.z,
+            }));
+        }
+
+        for def in BUTTONS
+            .iter()
+            .filter(|d| fits(d.sheet, d.normal) && fits(d.sheet, d.pressed))
+        {
+            let normal = format!("{}_normal", def.id);
+            let pressed = format!("{}_pressed", def.id);
+            assets.insert(normal.clone(), atlas(def.sheet, def.normal));
+            assets.insert(pressed.clone(), atlas(def.sheet, def.pressed));
+            parts.push(json!({
+                "id": def.id,
+                "type": "button",
+                "x": def.pos.0,
+                "y": def.pos.1,
+                "width": def.normal[2],
+                "height": def.normal[3],
+                "z": 2,
+                "action": def.id,
+                "draw": { "normal": normal, "hover": normal, "pressed": pressed },
+            }));
+        }
+
+        for def in TOGGLES
+            .iter()
+            .filter(|d| fits(d.sheet, d.off) && fits(d.sheet, d.on))
+        {
+            let off = format!("{}_off", def.id);
+            let on = format!("{}_on", def.id);
+            assets.insert(off.clone(), atlas(def.sheet, def.off));
+            assets.insert(on.clone(), atlas(def.sheet, def.on));
+            parts.push(json!({
+                "id": def.id,
+                "type": "checkbox",
+                "x": def.pos.0,
+                "y": def.pos.1,
+                "width": def.off[2],
+                "height": def.off[3],
+                "z": 2,
+                "binding": def.id,
+                "checkbox_draw": { "unchecked": off, "checked": on },
+            }));
+        }
+
+        let text_color = format!("0x{:06X}", text_color);
+        parts.push(json!({
+            "id": "song_title",
+            "type": "static_text",
+            "x": 111,
+            "y": 24,
+            "width": 154,
+            "height": 12,
+            "z": 2,
+            "content": name,
+            "font_size": 10.0,
+            "text_color": text_color,
+        }));
+        parts.push(json!({
+            "id": "time",
+            "type": "static_text",
+            "x": 48,
+            "y": 26,
+            "width": 63,
+            "height": 13,
+            "z": 2,
+            "content": "00:00",
+            "font_size": 12.0,
+            "text_color": text_color,
+            "text_align": "right",
+        }));
+
+        let skin_json: Value = json!({
+            "skin": { "name": name, "author": "Winamp import", "version": "1.0.0" },
+            "window": { "width": MAIN_WIDTH, "height": MAIN_HEIGHT, "resizable": false },
+            "assets": assets,
+            "parts": parts,
+        });
+
+        let skin_path = out_dir.join("skin.json");
+        fs::write(&skin_path, serde_json::to_string_pretty(&skin_json)?)?;
+
+        Skin::load(&skin_path)
+    }
+}
+
+/// Read every file in the archive, keyed by lowercase file name.
+/// Skins are often zipped inside a folder and with inconsistent casing.
+fn read_archive<R: Read + Seek>(reader: R) -> Result<HashMap<String, Vec<u8>>, SkinError> {
+    let mut archive = ZipArchive::new(reader)?;
+    let mut files = HashMap::new();
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry
+            .name()?
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes)?;
+        files.insert(name, bytes);
+    }
+
+    Ok(files)
+}
+
+/// Look up a color (e.g. `Normal=#00FF00`) in the `[Text]` section of `pledit.txt`.
+fn pledit_color(content: &str, key: &str) -> Option<u32> {
+    let mut in_text = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_text = line.eq_ignore_ascii_case("[text]");
+            continue;
+        }
+        if !in_text {
+            continue;
+        }
+        if let Some((k, v)) = line.split_once('=')
+            && k.trim().eq_ignore_ascii_case(key)
+        {
+            let hex = v.trim().trim_start_matches('#');
+            return u32::from_str_radix(hex.get(..6)?, 16).ok();
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::skin::{LoadedSkin, SkinBuilder};
+    use std::io::{Cursor, Write};
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    fn bmp(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        RgbaImage::new(width, height)
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Bmp)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_import_wsz_maps_sheets_to_parts() {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default();
+        zip.start_file("MySkin/Main.bmp", options).unwrap();
+        zip.write_all(&bmp(MAIN_WIDTH, MAIN_HEIGHT)).unwrap();
+        zip.start_file("MySkin/cbuttons.bmp", options).unwrap();
+        zip.write_all(&bmp(136, 36)).unwrap();
+        zip.start_file("MySkin/pledit.txt", options).unwrap();
+        zip.write_all(b"[Text]\r\nNormal=#FFCC00\r\nFont=Arial\r\n")
+            .unwrap();
+        let archive = zip.finish().unwrap();
+
+        let out_dir = std::env::temp_dir().join(format!("crix_wsz_test_{}", std::process::id()));
+        let skin = Skin::import_wsz_from(archive, "MySkin", &out_dir).unwrap();
+
+        assert_eq!(skin.window.width, MAIN_WIDTH);
+        assert!(skin.assets.contains_key("play_pressed"));
+        assert!(!skin.assets.contains_key("close_normal"));
+        let ids: Vec<_> = skin.parts.iter().map(|p| p.id.as_str()).collect();
+        assert!(ids.contains(&"main"));
+        assert!(ids.contains(&"eject"));
+        let title = skin.parts.iter().find(|p| p.id == "song_title").unwrap();
+        assert_eq!(title.text_color, Some(0xFFCC00));
+
+        // The generated skin loads and builds like a hand-written one
+        let loaded = LoadedSkin::load(&out_dir.join("skin.json")).unwrap();
+        assert!(SkinBuilder::build(&loaded).is_ok());
+
+        fs::remove_dir_all(&out_dir).ok();
+    }
+
+    #[test]
+    fn test_import_wsz_requires_main_bitmap() {
+        let zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let archive = zip.finish().unwrap();
+        let out_dir = std::env::temp_dir().join("crix_wsz_test_missing");
+        assert!(matches!(
+            Skin::import_wsz_from(archive, "Empty", &out_dir),
+            Err(SkinError::AssetNotFound(_))
+        ));
+    }
+}
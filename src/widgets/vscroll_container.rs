@@ -162,7 +162,7 @@ impl Widget for VScrollContainer {
             self.viewport_width(),
             self.viewport_height(),
         );
-        canvas.set_clip(Some(viewport));
+        canvas.push_clip(viewport);
 
         // Draw child with scroll offset
         if let Some(ref child) = self.child {
@@ -175,8 +175,8 @@ impl Widget for VScrollContainer {
             child.draw(canvas, &child_bounds, WidgetState::default());
         }
 
-        // Clear clip
-        canvas.set_clip(None);
+        // Restore the previous clip
+        canvas.pop_clip();
     }
 
     fn preferred_size(&self) -> (u32, u32) {